@@ -0,0 +1,52 @@
+//! # Events Module - Eventos del contrato de gobernanza
+//!
+//! Cada propuesta, voto, ejecución y cancelación publica un evento para que
+//! el frontend y los indexadores sigan las decisiones de la comunidad sin
+//! leer el almacenamiento del contrato.
+//!
+//! ## Formato:
+//! - Topics: `[nombre, "v1", ...campos #[topic]]`
+//! - Data: mapa con el resto de los campos (nombre -> valor)
+
+use soroban_sdk::{contractevent, Address};
+
+use crate::ProposalAction;
+
+/// Se creó una propuesta
+#[contractevent(topics = ["proposal_created", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProposalCreated {
+    #[topic]
+    pub proposal_id: u32,
+    pub proposer: Address,
+    pub action: ProposalAction,
+    pub end_time: u64,
+}
+
+/// Un miembro votó una propuesta con el peso de su participación al crearla
+#[contractevent(topics = ["vote_cast", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VoteCast {
+    #[topic]
+    pub proposal_id: u32,
+    pub voter: Address,
+    pub support: bool,
+    pub weight: u32,
+}
+
+/// Una propuesta aprobada se ejecutó
+#[contractevent(topics = ["proposal_executed", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProposalExecuted {
+    #[topic]
+    pub proposal_id: u32,
+}
+
+/// Una propuesta fue cancelada
+#[contractevent(topics = ["proposal_canceled", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProposalCanceled {
+    #[topic]
+    pub proposal_id: u32,
+    pub caller: Address,
+}
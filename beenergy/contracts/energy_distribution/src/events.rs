@@ -0,0 +1,260 @@
+//! # Events Module - Eventos del contrato de distribución
+//!
+//! Cada cambio de estado publica un evento para que el frontend (página de
+//! actividad) y cualquier indexador de Stellar puedan reconstruir el historial
+//! sin leer el almacenamiento del contrato.
+//!
+//! ## Formato:
+//! - Topics: `[nombre, "v1", ...campos #[topic]]`
+//! - Data: mapa con el resto de los campos (nombre -> valor)
+//! - Si el contenido de un evento cambia, se publica con un nuevo sufijo de
+//!   versión (`"v2"`) en lugar de modificar el existente
+
+use soroban_sdk::{contractevent, crypto::bls12_381::G1Affine, Address, BytesN, Vec};
+
+use crate::MultisigAction;
+
+/// El contrato fue inicializado
+#[contractevent(topics = ["initialized", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Initialized {
+    pub admin: Address,
+    pub token_contract: Address,
+    pub required_approvals: u32,
+}
+
+/// La lista de miembros y sus participaciones fue reemplazada
+/// (multi-firma, acción pendiente aprobada o gobernanza)
+#[contractevent(topics = ["members_updated", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MembersUpdated {
+    pub members: Vec<Address>,
+    pub shares_bps: Vec<u32>,
+}
+
+/// Las participaciones legacy fueron migradas a puntos básicos (`migrate`, esquema 1 → 2)
+#[contractevent(topics = ["shares_migrated", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SharesMigrated {
+    pub migrated: u32,
+}
+
+/// Se configuró el contrato de gobernanza
+#[contractevent(topics = ["governance_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GovernanceSet {
+    pub governance: Address,
+}
+
+/// Se propuso una acción multi-firma
+#[contractevent(topics = ["action_proposed", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionProposed {
+    #[topic]
+    pub action_id: u32,
+    pub proposer: Address,
+    pub action: MultisigAction,
+    pub expires_at: u64,
+}
+
+/// Un miembro aprobó una acción multi-firma pendiente
+#[contractevent(topics = ["action_approved", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionApproved {
+    #[topic]
+    pub action_id: u32,
+    pub approver: Address,
+}
+
+/// Una acción multi-firma alcanzó las aprobaciones y se ejecutó
+#[contractevent(topics = ["action_executed", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionExecuted {
+    #[topic]
+    pub action_id: u32,
+}
+
+/// Una acción multi-firma alcanzó las aprobaciones pero ya no se podía
+/// aplicar (la membresía cambió desde que se propuso) y se descartó
+#[contractevent(topics = ["action_dropped", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionDropped {
+    #[topic]
+    pub action_id: u32,
+}
+
+/// Una acción multi-firma pendiente fue cancelada
+#[contractevent(topics = ["action_canceled", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionCanceled {
+    #[topic]
+    pub action_id: u32,
+    pub caller: Address,
+}
+
+/// Se registró generación y se mintearon tokens a los miembros
+///
+/// `members` y `amounts` están alineados: `amounts[i]` es lo minteado a
+/// `members[i]` (puede ser 0 si su parte redondeó a cero).
+#[contractevent(topics = ["generation_recorded", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GenerationRecorded {
+    #[topic]
+    pub period: u64,
+    pub kwh: i128,
+    pub members: Vec<Address>,
+    pub amounts: Vec<i128>,
+}
+
+/// Se registró generación en modo privacidad
+///
+/// Reemplaza a `GenerationRecorded` mientras el modo de privacidad está
+/// habilitado: solo publica el total, no el reparto por miembro.
+/// Los eventos `mint` del token siguen publicando lo minteado a cada miembro.
+#[contractevent(topics = ["generation_total", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GenerationTotalRecorded {
+    #[topic]
+    pub period: u64,
+    pub kwh: i128,
+}
+
+/// Se registró un medidor
+#[contractevent(topics = ["meter_registered", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MeterRegistered {
+    #[topic]
+    pub meter: BytesN<32>,
+    pub initial_cumulative_kwh: i128,
+}
+
+/// Se desactivó un medidor
+#[contractevent(topics = ["meter_deactivated", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MeterDeactivated {
+    #[topic]
+    pub meter: BytesN<32>,
+}
+
+/// Se aceptó una lectura firmada de un medidor
+/// (la distribución se publica aparte como `GenerationRecorded`)
+#[contractevent(topics = ["meter_reading", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MeterReadingAccepted {
+    #[topic]
+    pub meter: BytesN<32>,
+    pub interval_start: u64,
+    pub interval_end: u64,
+    pub cumulative_kwh: i128,
+    pub delta: i128,
+}
+
+/// Se pausó el registro de generación y los cambios de membresía
+#[contractevent(topics = ["paused", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Paused {
+    pub caller: Address,
+}
+
+/// Se reanudó el contrato
+#[contractevent(topics = ["unpaused", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Unpaused {
+    pub caller: Address,
+}
+
+/// Se cambió el modo de privacidad
+#[contractevent(topics = ["privacy_toggled", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrivacyToggled {
+    pub enabled: bool,
+}
+
+/// Un usuario agregó un commitment de consumo privado a su historial
+#[contractevent(topics = ["commitment_stored", "v2"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentStoredV2 {
+    #[topic]
+    pub user: Address,
+    #[topic]
+    pub period: u64,
+    pub index: u32,
+    pub commitment: BytesN<32>,
+}
+
+/// El código del contrato fue actualizado
+#[contractevent(topics = ["upgraded", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Upgraded {
+    pub new_wasm_hash: BytesN<32>,
+}
+
+/// El almacenamiento fue migrado a una nueva versión de esquema
+#[contractevent(topics = ["migrated", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Migrated {
+    pub from_version: u32,
+    pub to_version: u32,
+}
+
+/// Se configuró la verification key Groth16 del circuito de consumo
+#[contractevent(topics = ["verification_key_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerificationKeySet {
+    pub public_inputs: u32,
+}
+
+/// Un miembro guardó un commitment Pedersen de consumo para un período
+///
+/// `aggregate` es la suma de los commitments del período después de este.
+#[contractevent(topics = ["consumption_committed", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsumptionCommitted {
+    #[topic]
+    pub user: Address,
+    #[topic]
+    pub period: u64,
+    pub commitment: G1Affine,
+    pub aggregate: G1Affine,
+}
+
+/// Un miembro comprometió el consumo de un período para liquidarlo
+#[contractevent(topics = ["settlement_committed", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementCommitted {
+    #[topic]
+    pub user: Address,
+    #[topic]
+    pub period: u64,
+    pub commitment: BytesN<32>,
+    pub deadline: u64,
+}
+
+/// Se liquidó el consumo de un período y se quemó HDROP
+///
+/// `penalized` es `true` si el miembro no reveló a tiempo y se quemó lo
+/// asignado en el período.
+#[contractevent(topics = ["consumption_settled", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsumptionSettled {
+    #[topic]
+    pub user: Address,
+    #[topic]
+    pub period: u64,
+    pub amount: i128,
+    pub penalized: bool,
+}
+
+/// Se cerró una liquidación en modo privacidad
+///
+/// Reemplaza a `ConsumptionSettled` mientras el modo de privacidad está
+/// habilitado: no publica la cantidad quemada.
+#[contractevent(topics = ["settlement_closed", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementClosed {
+    #[topic]
+    pub user: Address,
+    #[topic]
+    pub period: u64,
+    pub penalized: bool,
+}
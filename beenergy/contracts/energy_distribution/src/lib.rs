@@ -4,13 +4,43 @@
 //!
 //! Gestiona la distribución de energía generada por paneles solares comunitarios.
 //! - Registro multi-firma de miembros y sus porcentajes de propiedad
+//! - Control de acceso por roles (stellar-access) con transferencia de admin en dos pasos
 //! - Distribución automática de tokens HoneyDrop (HDROP) según generación de kWh
+//! - Lecturas de generación firmadas por medidores registrados (ed25519)
+//! - Historial de generación por período (franjas horarias) consultable por rango
+//! - Registro por miembro de lo asignado, independiente del ledger del token
+//! - Eventos versionados para cada cambio de estado (ver `events`)
+//! - Datos por miembro en almacenamiento persistente con TTL (ver `storage`)
+//! - Actualizable (`upgrade`) con versión de esquema y migración (`migrate`)
+//! - Pausa de emergencia (`PAUSER_ROLE`) de la generación y la membresía
 //! - Integración con token contract para minteo/quema
-//! - Sistema de privacidad con commitments (ZK proofs simulados)
-
-use soroban_sdk::{contract, contractimpl, contracttype, contracterror, Address, Bytes, BytesN, Env, Vec};
-
-mod privacy;
+//! - Privacidad: commitments de consumo y proofs Groth16 (BLS12-381) de
+//!   `consumido <= asignado` verificados on-chain (ver `privacy`)
+//! - Commitments Pedersen por período con agregado que un auditor puede abrir
+//! - Liquidación de consumo commit-reveal que quema HDROP al revelar
+//! - Modo de privacidad (admin con `PRIVACY_ROLE` o gobernanza): habilita los
+//!   commitments y oculta el reparto por miembro en los eventos de este
+//!   contrato; el token y las consultas por miembro siguen mostrando cantidades
+
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype,
+    crypto::bls12_381::{Fr, G1Affine},
+    panic_with_error, symbol_short, Address, BytesN, Env, Symbol, Val, Vec,
+};
+use stellar_access::access_control::{self as access_control, AccessControl};
+use stellar_macros::default_impl;
+
+pub mod events;
+mod meter;
+pub mod privacy;
+mod split;
+pub mod storage;
+
+pub use meter::{MeterReading, MeterState};
+pub use privacy::{
+    AggregateCommitment, CommitmentRecord, ConsumptionCommitment, ConsumptionOpening,
+    Groth16Proof, VerificationKey,
+};
 
 /// Errores del contrato de distribución de energía
 #[contracterror]
@@ -19,33 +49,216 @@ mod privacy;
 pub enum DistributionError {
     /// No hay suficientes firmantes (se requieren más aprobaciones)
     NotEnoughApprovers = 1,
-    /// La cantidad de miembros y porcentajes no coincide
-    MemberPercentMismatch = 2,
-    /// Los porcentajes no suman 100%
-    PercentsMustSumTo100 = 3,
+    /// La cantidad de miembros y participaciones no coincide
+    MemberShareMismatch = 2,
+    /// Las participaciones no suman 10_000 puntos básicos (100%)
+    SharesMustSumTo10000 = 3,
     /// Los miembros aún no han sido inicializados
     MembersNotInitialized = 4,
+    /// Una dirección aparece más de una vez en la lista de miembros
+    DuplicateMember = 5,
+    /// No hay contrato de gobernanza configurado
+    GovernanceNotSet = 6,
+    /// La dirección no es miembro de la comunidad
+    NotAMember = 7,
+    /// Un aprobador aparece más de una vez
+    DuplicateApprover = 8,
+    /// La acción pendiente no existe (o ya fue ejecutada/cancelada)
+    ActionNotFound = 9,
+    /// La acción pendiente expiró
+    ActionExpired = 10,
+    /// El miembro ya aprobó esta acción
+    AlreadyApproved = 11,
+    /// Duración de la acción inválida (0 o mayor al máximo)
+    InvalidExpiry = 12,
+    /// El llamador no tiene permiso para esta operación
+    NotAuthorized = 13,
+    /// Cantidad de aprobaciones requeridas inválida
+    InvalidApprovalThreshold = 14,
+    /// El medidor no está registrado o fue desactivado
+    MeterNotRegistered = 15,
+    /// El medidor ya está registrado
+    MeterAlreadyRegistered = 16,
+    /// Intervalo de lectura inválido (fin <= inicio o en el futuro)
+    InvalidInterval = 17,
+    /// La lectura es anterior o se superpone con la última aceptada (replay)
+    StaleReading = 18,
+    /// La lectura acumulada es negativa o menor a la anterior
+    InvalidReading = 19,
+    /// El período ya tiene una generación registrada
+    PeriodAlreadyRecorded = 20,
+    /// El período todavía no comenzó
+    InvalidPeriod = 21,
+    /// Rango de consulta inválido (desde > hasta o demasiado grande)
+    InvalidRange = 22,
+    /// El almacenamiento es de una versión de esquema más nueva que el código
+    UnsupportedSchemaVersion = 23,
+    /// La verification key no corresponde al circuito de consumo
+    InvalidVerificationKey = 24,
+    /// No hay verification key configurada
+    VerificationKeyNotSet = 25,
+    /// El usuario no tiene un commitment de consumo guardado
+    CommitmentNotFound = 26,
+    /// El miembro ya guardó un commitment para este período
+    CommitmentAlreadyRecorded = 27,
+    /// El commitment no es un punto válido de G1
+    InvalidCommitment = 28,
+    /// La liquidación ya fue revelada o penalizada
+    SettlementClosed = 29,
+    /// Venció el plazo para revelar el consumo
+    RevealDeadlinePassed = 30,
+    /// El plazo para revelar todavía no venció
+    RevealWindowOpen = 31,
+    /// Los datos revelados no corresponden al commitment
+    InvalidReveal = 32,
+    /// El modo de privacidad no está habilitado
+    PrivacyDisabled = 33,
+    /// La cantidad debe ser mayor que cero
+    InvalidAmount = 34,
+    /// El resultado no entra en un i128
+    ArithmeticOverflow = 35,
+    /// El contrato está pausado
+    ContractPaused = 36,
+    /// No hay admin configurado
+    AdminNotSet = 37,
+    /// El miembro no tiene HDROP suficiente para quemar el consumo revelado
+    InsufficientBalance = 38,
 }
 
+/// Rol que registra generación y administra los medidores
+pub const REPORTER_ROLE: Symbol = symbol_short!("reporter");
+
+/// Rol que administra el modo de privacidad
+pub const PRIVACY_ROLE: Symbol = symbol_short!("privacy");
+
+/// Rol que administra la configuración de membresía (gobernanza, migración,
+/// cancelación de acciones multi-firma)
+pub const MEMBERS_ROLE: Symbol = symbol_short!("members");
+
+/// Rol que puede pausar la generación y los cambios de membresía
+pub const PAUSER_ROLE: Symbol = symbol_short!("pauser");
+
+/// Versión del esquema de almacenamiento que usa este código
+///
+/// - 1: datos por miembro en `instance()` (contratos sin versión guardada)
+/// - 2: datos por miembro en almacenamiento persistente con TTL
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Total de las participaciones en puntos básicos (10_000 = 100%)
+pub const TOTAL_SHARES_BPS: u32 = 10_000;
+
+/// Duración de un período de generación: franjas de una hora.
+/// El período de un timestamp es `timestamp / PERIOD_SECONDS`.
+pub const PERIOD_SECONDS: u64 = 3_600;
+
+/// Cantidad máxima de períodos por consulta de historial (31 días)
+pub const MAX_HISTORY_RANGE: u64 = 31 * 24;
+
+/// Cantidad máxima de elementos por página en las consultas paginadas
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+/// Plazo para revelar el consumo de un período, contado desde el fin del
+/// período (7 días)
+pub const REVEAL_WINDOW: u64 = 7 * 24 * 60 * 60;
+
+/// Duración máxima de una acción multi-firma pendiente (30 días)
+pub const MAX_ACTION_DURATION: u64 = 30 * 24 * 60 * 60;
+
 #[contracttype]
 #[derive(Clone)]
 pub struct Member {
     pub address: Address,
-    pub percent: u32,
+    pub share_bps: u32,         // Participación en puntos básicos (2_500 = 25%)
+}
+
+/// Generación registrada en un período
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GenerationRecord {
+    pub period: u64,        // Franja horaria (timestamp / PERIOD_SECONDS)
+    pub kwh: i128,          // kWh generados en el período (7 decimales)
+    pub recorded_at: u64,   // Timestamp del último registro en el período
+}
+
+/// Tokens asignados a un miembro en un período
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PeriodAllocation {
+    pub period: u64,
+    pub amount: i128,
+}
+
+/// Estado de la liquidación de consumo de un período
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SettlementStatus {
+    /// Esperando que el miembro revele el consumo
+    Pending,
+    /// Consumo revelado y quemado
+    Revealed(i128),
+    /// No se reveló a tiempo: se quemó lo asignado en el período
+    Penalized(i128),
+}
+
+/// Liquidación commit-reveal del consumo de un miembro en un período
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Settlement {
+    pub commitment: BytesN<32>, // SHA256(user_data), ver `privacy`
+    pub committed_at: u64,
+    pub deadline: u64,          // Último timestamp para revelar
+    pub status: SettlementStatus,
+}
+
+/// Acción que requiere `RequiredApprovals` aprobaciones de miembros
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MultisigAction {
+    /// Reemplaza la lista de miembros y sus participaciones (puntos básicos)
+    SetMembers(Vec<Address>, Vec<u32>),
+    /// Cambia la cantidad de aprobaciones requeridas
+    SetRequiredApprovals(u32),
+}
+
+/// Acción multi-firma pendiente, aprobada de forma asíncrona por los miembros
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingAction {
+    pub id: u32,
+    pub proposer: Address,
+    pub action: MultisigAction,
+    pub approvals: Vec<Address>,
+    pub expires_at: u64,
 }
 
 #[contracttype]
 pub enum DataKey {
-    Admin,
     TokenContract,          // Dirección del contrato de HoneyDrop
     RequiredApprovals,
     MembersInitialized,
-    Member(Address),
-    MemberPercent(Address),
+    Member(Address),        // Persistente: si la dirección es miembro
+    MemberPercent(Address), // Legacy (instance): porcentaje entero (0-100), ver `migrate`
+    MemberShare(Address),   // Persistente: participación en puntos básicos
     MemberList,             // Lista de todas las direcciones de miembros
     TotalGenerated,         // Total de kWh generados históricamente
     PrivacyEnabled,         // Si el modo de privacidad está habilitado
-    UserCommitment(Address), // Commitment de consumo privado por usuario
+    UserCommitment(Address), // Persistente: commitment v0 guardado con el esquema 1 (solo lectura)
+    Governance,             // Contrato CommunityGovernance que puede redistribuir la propiedad
+    PendingActionCount,     // Contador para ids de acciones multi-firma
+    PendingAction(u32),     // Acción multi-firma esperando aprobaciones
+    Meter(BytesN<32>),      // Estado de un medidor (clave pública ed25519)
+    Generation(u64),        // Generación registrada por período
+    MeteredGeneration(u64), // El período se registra con lecturas de medidores
+    MemberAllocated(Address),          // Total histórico asignado a un miembro
+    MemberAllocation(Address, u64),    // Asignado a un miembro en un período
+    SchemaVersion,          // Versión del esquema de almacenamiento (ver `SCHEMA_VERSION`)
+    VerificationKey,        // Verification key Groth16 del circuito de consumo
+    ConsumptionCommitment(Address, u64), // Persistente: commitment Pedersen de un miembro en un período
+    AggregateCommitment(u64),            // Persistente: suma de los commitments Pedersen del período
+    CommitmentCount(Address, u64),       // Persistente: commitments de un usuario en un período
+    CommitmentRecord(Address, u64, u32), // Persistente: commitment `n` de un usuario en un período
+    Settlement(Address, u64),            // Persistente: liquidación de consumo de un miembro en un período
+    Paused,                 // Si el contrato está pausado (ver `pause`)
 }
 
 #[contract]
@@ -59,21 +272,38 @@ mod energy_token_interface {
     pub trait EnergyTokenTrait {
         /// Mintea tokens de energía a una dirección
         fn mint_energy(env: Env, to: Address, amount: i128, minter: Address);
+        /// Quema consumo liquidado (sin la firma de `from`, requiere rol de liquidación)
+        fn burn_consumption(env: Env, from: Address, amount: i128, settler: Address);
+        /// Balance de una dirección
+        fn balance(env: Env, id: Address) -> i128;
     }
 }
 
 #[contractimpl]
 impl EnergyDistribution {
-    /// Inicializa el contrato de distribución
+    /// Constructor del contrato
+    ///
+    /// Se ejecuta una sola vez al desplegar, así nadie puede volver a
+    /// inicializar el contrato para reemplazar al admin.
+    ///
+    /// El admin recibe los roles `REPORTER_ROLE`, `PRIVACY_ROLE`,
+    /// `MEMBERS_ROLE` y `PAUSER_ROLE`; después puede otorgarlos a otras
+    /// cuentas y revocarse los propios con `grant_role` / `revoke_role`.
     ///
     /// # Argumentos
-    /// * `admin` - Administrador del contrato
+    /// * `admin` - Administrador de roles del contrato
     /// * `token_contract` - Dirección del contrato HoneyDrop (HDROP)
     /// * `required_approvals` - Número de firmas requeridas para agregar miembros
-    pub fn initialize(env: Env, admin: Address, token_contract: Address, required_approvals: u32) {
-        admin.require_auth();
+    pub fn __constructor(env: Env, admin: Address, token_contract: Address, required_approvals: u32) {
+        if required_approvals == 0 {
+            panic_with_error!(&env, DistributionError::InvalidApprovalThreshold);
+        }
+
+        access_control::set_admin(&env, &admin);
+        for role in [REPORTER_ROLE, PRIVACY_ROLE, MEMBERS_ROLE, PAUSER_ROLE] {
+            access_control::grant_role_no_auth(&env, &admin, &admin, &role);
+        }
 
-        env.storage().instance().set(&DataKey::Admin, &admin);
         env.storage()
             .instance()
             .set(&DataKey::TokenContract, &token_contract);
@@ -86,20 +316,37 @@ impl EnergyDistribution {
         env.storage()
             .instance()
             .set(&DataKey::TotalGenerated, &0i128);
+        env.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+        storage::extend_instance(&env);
+
+        events::Initialized {
+            admin,
+            token_contract,
+            required_approvals,
+        }
+        .publish(&env);
     }
 
     /// Agrega miembros con multi-firma
     ///
+    /// Todos los aprobadores firman la misma transacción. Una vez que la
+    /// comunidad está inicializada, los aprobadores deben ser miembros; para
+    /// aprobaciones en transacciones separadas usar `propose_action`.
+    ///
     /// # Argumentos
     /// * `approvers` - Lista de aprobadores que firman la transacción
     /// * `members` - Lista de direcciones de miembros
-    /// * `percents` - Lista de porcentajes de propiedad (deben sumar 100)
+    /// * `shares_bps` - Participaciones en puntos básicos (deben sumar 10_000)
     pub fn add_members_multisig(
         env: Env,
         approvers: Vec<Address>,
         members: Vec<Address>,
-        percents: Vec<u32>,
+        shares_bps: Vec<u32>,
     ) -> Result<(), DistributionError> {
+        Self::require_not_paused(&env)?;
+
         // Verificar que hay suficientes aprobadores
         let required: u32 = env
             .storage()
@@ -111,371 +358,3405 @@ impl EnergyDistribution {
             return Err(DistributionError::NotEnoughApprovers);
         }
 
+        let initialized = Self::are_members_initialized(env.clone());
+
         // Requerir autenticación de todos los aprobadores
-        for approver in approvers.iter() {
+        for i in 0..approvers.len() {
+            let approver = approvers.get(i).unwrap();
+            if approvers.last_index_of(&approver) != Some(i) {
+                return Err(DistributionError::DuplicateApprover);
+            }
+            if initialized && !Self::is_member(env.clone(), approver.clone()) {
+                return Err(DistributionError::NotAMember);
+            }
             approver.require_auth();
         }
 
-        // Verificar que members y percents tienen la misma longitud
-        if members.len() != percents.len() {
-            return Err(DistributionError::MemberPercentMismatch);
-        }
+        Self::write_members(&env, &members, &shares_bps)
+    }
 
-        // Verificar que los porcentajes suman 100
-        let total: u32 = percents.iter().sum();
-        if total != 100 {
-            return Err(DistributionError::PercentsMustSumTo100);
+    /// Propone una acción multi-firma que los miembros aprueban en
+    /// transacciones separadas
+    ///
+    /// La propuesta cuenta como la primera aprobación. Si con ella se alcanzan
+    /// las aprobaciones requeridas, la acción se ejecuta inmediatamente.
+    ///
+    /// # Argumentos
+    /// * `proposer` - Miembro que propone la acción
+    /// * `action` - Acción a ejecutar
+    /// * `duration` - Segundos hasta que la acción expire (máx. 30 días)
+    ///
+    /// # Retorna
+    /// El id de la acción pendiente
+    pub fn propose_action(
+        env: Env,
+        proposer: Address,
+        action: MultisigAction,
+        duration: u64,
+    ) -> Result<u32, DistributionError> {
+        proposer.require_auth();
+        Self::require_not_paused(&env)?;
+
+        if !Self::are_members_initialized(env.clone()) {
+            return Err(DistributionError::MembersNotInitialized);
         }
-
-        // Crear lista de miembros
-        let mut member_list: Vec<Address> = Vec::new(&env);
-
-        // Guardar miembros y sus porcentajes
-        for i in 0..members.len() {
-            let member = members.get(i).unwrap();
-            let percent = percents.get(i).unwrap();
-
-            env.storage()
-                .instance()
-                .set(&DataKey::Member(member.clone()), &true);
-            env.storage()
-                .instance()
-                .set(&DataKey::MemberPercent(member.clone()), &percent);
-
-            member_list.push_back(member);
+        if !Self::is_member(env.clone(), proposer.clone()) {
+            return Err(DistributionError::NotAMember);
+        }
+        if duration == 0 || duration > MAX_ACTION_DURATION {
+            return Err(DistributionError::InvalidExpiry);
+        }
+        if let MultisigAction::SetRequiredApprovals(required) = &action {
+            let member_count = Self::get_member_list(env.clone()).len();
+            if *required == 0 || *required > member_count {
+                return Err(DistributionError::InvalidApprovalThreshold);
+            }
         }
 
-        // Guardar lista de miembros
-        env.storage()
+        let id: u32 = env
+            .storage()
             .instance()
-            .set(&DataKey::MemberList, &member_list);
-
+            .get(&DataKey::PendingActionCount)
+            .unwrap_or(0);
         env.storage()
             .instance()
-            .set(&DataKey::MembersInitialized, &true);
+            .set(&DataKey::PendingActionCount, &(id + 1));
+
+        let pending = PendingAction {
+            id,
+            proposer: proposer.clone(),
+            action,
+            approvals: Vec::from_array(&env, [proposer]),
+            expires_at: env.ledger().timestamp() + duration,
+        };
+
+        events::ActionProposed {
+            action_id: id,
+            proposer: pending.proposer.clone(),
+            action: pending.action.clone(),
+            expires_at: pending.expires_at,
+        }
+        .publish(&env);
 
-        Ok(())
+        Self::execute_if_approved(&env, pending)?;
+
+        Ok(id)
     }
 
-    /// Registra generación de energía y distribuye tokens HoneyDrop
+    /// Aprueba una acción multi-firma pendiente
+    ///
+    /// Cuando la acción alcanza `RequiredApprovals` aprobaciones de miembros
+    /// actuales, se ejecuta y se elimina del almacenamiento.
     ///
     /// # Argumentos
-    /// * `kwh_generated` - Cantidad de kWh generados (con 7 decimales, ej: 100_0000000 = 100 kWh)
+    /// * `approver` - Miembro que aprueba
+    /// * `action_id` - Acción a aprobar
     ///
-    /// Esta función:
-    /// 1. Calcula cuántos tokens le corresponden a cada miembro según su %
-    /// 2. Mintea tokens HoneyDrop a cada miembro
-    /// 3. Actualiza el total generado
-    pub fn record_generation(env: Env, kwh_generated: i128) -> Result<(), DistributionError> {
-        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
+    /// # Retorna
+    /// `true` si la acción se ejecutó con esta aprobación
+    pub fn approve_action(
+        env: Env,
+        approver: Address,
+        action_id: u32,
+    ) -> Result<bool, DistributionError> {
+        approver.require_auth();
+        Self::require_not_paused(&env)?;
 
-        // Verificar que los miembros estén inicializados
-        let initialized: bool = env
-            .storage()
-            .instance()
-            .get(&DataKey::MembersInitialized)
-            .unwrap_or(false);
+        let mut pending: PendingAction = storage::get(&env, &DataKey::PendingAction(action_id))
+            .ok_or(DistributionError::ActionNotFound)?;
 
-        if !initialized {
-            return Err(DistributionError::MembersNotInitialized);
+        if env.ledger().timestamp() >= pending.expires_at {
+            return Err(DistributionError::ActionExpired);
+        }
+        if !Self::is_member(env.clone(), approver.clone()) {
+            return Err(DistributionError::NotAMember);
+        }
+        if pending.approvals.contains(&approver) {
+            return Err(DistributionError::AlreadyApproved);
         }
 
-        // Obtener el contrato del token
-        let token_contract: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::TokenContract)
-            .unwrap();
+        pending.approvals.push_back(approver.clone());
 
-        // Obtener lista de miembros
-        let member_list: Vec<Address> = env
-            .storage()
-            .instance()
-            .get(&DataKey::MemberList)
-            .unwrap();
+        events::ActionApproved {
+            action_id,
+            approver,
+        }
+        .publish(&env);
 
-        // Crear cliente del token
-        let token_client = energy_token_interface::EnergyTokenClient::new(&env, &token_contract);
+        Self::execute_if_approved(&env, pending)
+    }
 
-        // Distribuir tokens a cada miembro según su porcentaje
-        for i in 0..member_list.len() {
-            let member = member_list.get(i).unwrap();
-            let percent: u32 = env
-                .storage()
-                .instance()
-                .get(&DataKey::MemberPercent(member.clone()))
-                .unwrap();
+    /// Cancela una acción multi-firma pendiente
+    /// Solo puede ser llamado por el proponente o una cuenta con `MEMBERS_ROLE`
+    pub fn cancel_action(
+        env: Env,
+        caller: Address,
+        action_id: u32,
+    ) -> Result<(), DistributionError> {
+        caller.require_auth();
 
-            // Calcular tokens a mintear: (kwh_generated * percent) / 100
-            let tokens_to_mint = (kwh_generated * percent as i128) / 100;
+        let pending: PendingAction = storage::get(&env, &DataKey::PendingAction(action_id))
+            .ok_or(DistributionError::ActionNotFound)?;
 
-            // Mintear tokens al miembro
-            // El contrato de distribución debe tener rol de MINTER en el token contract
-            token_client.mint_energy(&member, &tokens_to_mint, &env.current_contract_address());
+        if caller != pending.proposer
+            && access_control::has_role(&env, &caller, &MEMBERS_ROLE).is_none()
+        {
+            return Err(DistributionError::NotAuthorized);
         }
 
-        // Actualizar total generado
-        let current_total: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TotalGenerated)
-            .unwrap_or(0);
-        env.storage()
-            .instance()
-            .set(&DataKey::TotalGenerated, &(current_total + kwh_generated));
+        storage::remove(&env, &DataKey::PendingAction(action_id));
+
+        events::ActionCanceled { action_id, caller }.publish(&env);
 
         Ok(())
     }
 
-    // ========================================================================
-    // Privacy Functions (ZK Proof Simulation)
-    // ========================================================================
+    /// Configura el contrato de gobernanza que puede redistribuir la propiedad
+    /// Solo puede ser llamado por una cuenta con `MEMBERS_ROLE`
+    ///
+    /// # Argumentos
+    /// * `caller` - Cuenta con `MEMBERS_ROLE`
+    /// * `governance` - Dirección del contrato CommunityGovernance
+    pub fn set_governance(
+        env: Env,
+        caller: Address,
+        governance: Address,
+    ) -> Result<(), DistributionError> {
+        Self::require_role(&env, &caller, &MEMBERS_ROLE)?;
 
-    /// Habilita el modo de privacidad
-    /// Solo puede ser llamado por el admin
-    pub fn enable_privacy(env: Env) {
-        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
+        env.storage()
+            .instance()
+            .set(&DataKey::Governance, &governance);
 
-        env.storage().instance().set(&DataKey::PrivacyEnabled, &true);
+        events::GovernanceSet { governance }.publish(&env);
+
+        Ok(())
     }
 
-    /// Registra consumo de forma privada usando un commitment
+    /// Reemplaza la lista de miembros y sus participaciones
+    /// Solo puede ser llamado por el contrato de gobernanza, al ejecutar
+    /// una propuesta aprobada por los miembros actuales
     ///
-    /// # Privacidad:
-    /// En lugar de revelar la cantidad exacta consumida, el usuario
-    /// envía un "commitment" (hash) que prueba que consumió energía
-    /// sin revelar cuánto.
+    /// La nueva lista debe tener al menos `RequiredApprovals` miembros.
     ///
     /// # Argumentos
-    /// * `user` - Usuario que consumió energía
-    /// * `commitment` - Hash del consumo (SHA256 de: address + kwh + secret)
+    /// * `members` - Nueva lista de direcciones de miembros
+    /// * `shares_bps` - Nuevas participaciones en puntos básicos (deben sumar 10_000)
+    pub fn update_members(
+        env: Env,
+        members: Vec<Address>,
+        shares_bps: Vec<u32>,
+    ) -> Result<(), DistributionError> {
+        let governance: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Governance)
+            .ok_or(DistributionError::GovernanceNotSet)?;
+        governance.require_auth();
+        Self::require_not_paused(&env)?;
+
+        Self::write_members(&env, &members, &shares_bps)
+    }
+
+    /// Registra generación de energía y distribuye tokens HoneyDrop
+    /// Solo puede ser llamado por una cuenta con `REPORTER_ROLE`
     ///
-    /// # Cómo funciona:
-    /// 1. Usuario genera commitment off-chain: hash(address + consumed_kwh + secret)
-    /// 2. Envía commitment al contrato
-    /// 3. Contrato almacena el commitment sin conocer la cantidad
-    /// 4. Para demostrar consumo, usuario puede revelar datos más tarde
+    /// # Argumentos
+    /// * `reporter` - Cuenta con `REPORTER_ROLE`
+    /// * `period` - Franja horaria de la generación (`timestamp / PERIOD_SECONDS`)
+    /// * `kwh_generated` - Cantidad de kWh generados (con 7 decimales, ej: 100_0000000 = 100 kWh)
     ///
-    /// # Para Producción:
-    /// Reemplazar con ZK-SNARKs reales (Groth16) que permitan verificar
-    /// matemáticamente sin necesidad de revelar los datos.
-    pub fn record_private_consumption(
+    /// Cada período se puede registrar una sola vez, así una lectura enviada
+    /// dos veces no se distribuye dos veces. Un período tiene una sola
+    /// fuente: si ya recibió lecturas de medidores, tampoco se puede
+    /// registrar a mano (y viceversa, ver `submit_meter_reading`).
+    ///
+    /// Los tokens se reparten según la participación de cada miembro; la
+    /// suma minteada es siempre igual a `kwh_generated`.
+    pub fn record_generation(
         env: Env,
-        user: Address,
-        commitment: BytesN<32>,
+        reporter: Address,
+        period: u64,
+        kwh_generated: i128,
     ) -> Result<(), DistributionError> {
-        user.require_auth();
+        Self::require_role(&env, &reporter, &REPORTER_ROLE)?;
+        Self::require_not_paused(&env)?;
 
-        // Verificar que privacidad esté habilitada
-        let privacy_enabled: bool = env
-            .storage()
-            .instance()
-            .get(&DataKey::PrivacyEnabled)
-            .unwrap_or(false);
+        if kwh_generated <= 0 {
+            return Err(DistributionError::InvalidAmount);
+        }
+        if period > env.ledger().timestamp() / PERIOD_SECONDS {
+            return Err(DistributionError::InvalidPeriod);
+        }
+        if storage::has(&env, &DataKey::Generation(period)) {
+            return Err(DistributionError::PeriodAlreadyRecorded);
+        }
+
+        Self::distribute(&env, period, kwh_generated)
+    }
+
+    /// Registra un medidor que podrá enviar lecturas firmadas
+    /// Solo puede ser llamado por una cuenta con `REPORTER_ROLE`
+    ///
+    /// # Argumentos
+    /// * `caller` - Cuenta con `REPORTER_ROLE`
+    /// * `meter` - Clave pública ed25519 del medidor
+    /// * `initial_cumulative_kwh` - Lectura acumulada del medidor al instalarlo
+    pub fn register_meter(
+        env: Env,
+        caller: Address,
+        meter: BytesN<32>,
+        initial_cumulative_kwh: i128,
+    ) -> Result<(), DistributionError> {
+        Self::require_role(&env, &caller, &REPORTER_ROLE)?;
 
-        if !privacy_enabled {
-            // Si privacidad no está habilitada, podría fallar o simplemente ignorar
-            // Para la demo, lo permitimos igual
+        let key = DataKey::Meter(meter.clone());
+        if storage::has(&env, &key) {
+            return Err(DistributionError::MeterAlreadyRegistered);
+        }
+        if initial_cumulative_kwh < 0 {
+            return Err(DistributionError::InvalidReading);
         }
 
-        // Verificar que sea miembro
-        let is_member: bool = env
-            .storage()
-            .instance()
-            .get(&DataKey::Member(user.clone()))
-            .unwrap_or(false);
+        let state = MeterState {
+            active: true,
+            last_interval_end: 0,
+            last_cumulative_kwh: initial_cumulative_kwh,
+        };
+        storage::set(&env, &key, &state);
 
-        if !is_member {
-            return Err(DistributionError::MembersNotInitialized);
+        events::MeterRegistered {
+            meter,
+            initial_cumulative_kwh,
         }
+        .publish(&env);
 
-        // Almacenar commitment
-        env.storage()
-            .instance()
-            .set(&DataKey::UserCommitment(user.clone()), &commitment);
+        Ok(())
+    }
+
+    /// Desactiva un medidor (por ejemplo, si su clave fue comprometida)
+    /// Solo puede ser llamado por una cuenta con `REPORTER_ROLE`
+    pub fn deactivate_meter(
+        env: Env,
+        caller: Address,
+        meter: BytesN<32>,
+    ) -> Result<(), DistributionError> {
+        Self::require_role(&env, &caller, &REPORTER_ROLE)?;
+
+        let key = DataKey::Meter(meter.clone());
+        let mut state: MeterState =
+            storage::get(&env, &key).ok_or(DistributionError::MeterNotRegistered)?;
+
+        state.active = false;
+        storage::set(&env, &key, &state);
+
+        events::MeterDeactivated { meter }.publish(&env);
 
         Ok(())
     }
 
-    /// Verifica un commitment de consumo privado
+    /// Registra una lectura firmada por un medidor y distribuye la
+    /// generación nueva
     ///
-    /// NOTA: Esta función es solo para demostración.
-    /// En un sistema ZK real, NO necesitaríamos pasar user_data porque
-    /// el proof matemático se verificaría sin revelar información.
+    /// No requiere auth de ninguna cuenta: la firma del medidor es la
+    /// autorización. Cualquiera (por ejemplo un relayer) puede enviarla.
+    ///
+    /// La generación se suma al período en que empieza el intervalo; varios
+    /// medidores pueden aportar al mismo período, pero no a uno registrado a
+    /// mano con `record_generation` (`PeriodAlreadyRecorded`). Una lectura sin
+    /// generación nueva avanza el medidor sin registrar el período.
     ///
     /// # Argumentos
-    /// * `user` - Usuario cuyo commitment se verifica
-    /// * `user_data` - Datos originales (solo para demo, revelaría info en prod)
-    pub fn verify_private_consumption(
+    /// * `reading` - Lectura del medidor (ver `meter::reading_message`)
+    /// * `signature` - Firma ed25519 del medidor sobre la lectura
+    ///
+    /// # Retorna
+    /// Los kWh distribuidos (diferencia con la lectura anterior)
+    pub fn submit_meter_reading(
         env: Env,
-        user: Address,
-        user_data: Bytes,
-    ) -> bool {
-        // Obtener commitment almacenado
-        let stored_commitment: Option<BytesN<32>> = env
-            .storage()
-            .instance()
-            .get(&DataKey::UserCommitment(user));
+        reading: MeterReading,
+        signature: BytesN<64>,
+    ) -> Result<i128, DistributionError> {
+        Self::require_not_paused(&env)?;
 
-        match stored_commitment {
-            Some(commitment) => {
-                // Verificar usando el módulo de privacidad
-                privacy::verify_commitment(&env, &commitment, &user_data)
-            }
-            None => false,
+        let key = DataKey::Meter(reading.meter.clone());
+        let mut state: MeterState =
+            storage::get(&env, &key).ok_or(DistributionError::MeterNotRegistered)?;
+
+        if !state.active {
+            return Err(DistributionError::MeterNotRegistered);
+        }
+        if reading.interval_end <= reading.interval_start
+            || reading.interval_end > env.ledger().timestamp()
+        {
+            return Err(DistributionError::InvalidInterval);
+        }
+        if reading.interval_start < state.last_interval_end {
+            return Err(DistributionError::StaleReading);
+        }
+        if reading.cumulative_kwh < state.last_cumulative_kwh {
+            return Err(DistributionError::InvalidReading);
         }
+
+        meter::verify_reading(&env, &reading, &signature);
+
+        let period = reading.interval_start / PERIOD_SECONDS;
+        let metered_key = DataKey::MeteredGeneration(period);
+        if storage::has(&env, &DataKey::Generation(period)) && !storage::has(&env, &metered_key) {
+            return Err(DistributionError::PeriodAlreadyRecorded);
+        }
+
+        let delta = reading.cumulative_kwh - state.last_cumulative_kwh;
+
+        state.last_interval_end = reading.interval_end;
+        state.last_cumulative_kwh = reading.cumulative_kwh;
+        storage::set(&env, &key, &state);
+
+        events::MeterReadingAccepted {
+            meter: reading.meter.clone(),
+            interval_start: reading.interval_start,
+            interval_end: reading.interval_end,
+            cumulative_kwh: reading.cumulative_kwh,
+            delta,
+        }
+        .publish(&env);
+
+        if delta > 0 {
+            storage::set(&env, &metered_key, &true);
+            Self::distribute(&env, period, delta)?;
+        }
+
+        Ok(delta)
     }
 
-    /// Helper: Genera commitment off-chain (para testing/frontend)
+    /// Extiende el TTL de la instancia, de los datos de los miembros y de sus
+    /// registros por período en el rango indicado
+    /// Solo puede ser llamado por el admin
+    ///
+    /// Mantenimiento para comunidades con miembros que no interactúan con el
+    /// contrato durante meses (ver `storage`). Cada período suma lecturas a la
+    /// transacción: en comunidades grandes, usar rangos cortos. Los registros
+    /// de quienes ya no son miembros no se pueden enumerar y no se extienden.
     ///
-    /// En producción, esto se haría completamente off-chain en el frontend
-    /// con SnarkJS para generar ZK proofs reales.
-    pub fn generate_commitment_helper(
+    /// # Argumentos
+    /// * `from_period` - Primer período a extender
+    /// * `to_period` - Último período a extender (como máximo `MAX_HISTORY_RANGE` períodos)
+    ///
+    /// # Retorna
+    /// La cantidad de entradas persistentes extendidas
+    pub fn bump_storage(
         env: Env,
-        user_address_bytes: BytesN<32>,
-        consumed_kwh: i128,
-        secret: BytesN<32>,
-    ) -> BytesN<32> {
-        let user_data = privacy::hash_consumption_data(
-            &env,
-            &user_address_bytes,
-            consumed_kwh,
-            &secret,
-        );
+        from_period: u64,
+        to_period: u64,
+    ) -> Result<u32, DistributionError> {
+        let admin = access_control::get_admin(&env).ok_or(DistributionError::AdminNotSet)?;
+        admin.require_auth();
+
+        if from_period > to_period || to_period - from_period >= MAX_HISTORY_RANGE {
+            return Err(DistributionError::InvalidRange);
+        }
+
+        storage::extend_instance(&env);
+
+        let mut bumped = 0u32;
+        let mut bump = |key: DataKey| {
+            if storage::extend(&env, &key) {
+                bumped += 1;
+            }
+        };
+
+        let members = Self::get_member_list(env.clone());
+        for member in members.iter() {
+            bump(DataKey::Member(member.clone()));
+            bump(DataKey::MemberShare(member.clone()));
+            bump(DataKey::UserCommitment(member.clone()));
+            bump(DataKey::MemberAllocated(member));
+        }
+
+        for period in from_period..=to_period {
+            bump(DataKey::Generation(period));
+            bump(DataKey::MeteredGeneration(period));
+            bump(DataKey::AggregateCommitment(period));
+
+            for member in members.iter() {
+                bump(DataKey::MemberAllocation(member.clone(), period));
+                bump(DataKey::ConsumptionCommitment(member.clone(), period));
+                bump(DataKey::Settlement(member.clone(), period));
+
+                let count: u32 = Self::get_commitment_count(env.clone(), member.clone(), period);
+                bump(DataKey::CommitmentCount(member.clone(), period));
+                for index in 0..count {
+                    bump(DataKey::CommitmentRecord(member.clone(), period, index));
+                }
+            }
+        }
 
-        privacy::generate_commitment(&env, &user_data)
+        Ok(bumped)
     }
 
     // ========================================================================
-    // View Functions
+    // Upgrade Functions
     // ========================================================================
 
-    pub fn is_member(env: Env, address: Address) -> bool {
-        env.storage()
-            .instance()
-            .get(&DataKey::Member(address))
-            .unwrap_or(false)
-    }
+    /// Actualiza el código del contrato conservando su almacenamiento
+    /// Solo puede ser llamado por el admin o el contrato de gobernanza
+    ///
+    /// Si el nuevo código usa otra versión de esquema, llamar a `migrate`
+    /// a continuación.
+    ///
+    /// # Argumentos
+    /// * `caller` - Admin o contrato de gobernanza
+    /// * `new_wasm_hash` - Hash del WASM ya subido a la red
+    pub fn upgrade(
+        env: Env,
+        caller: Address,
+        new_wasm_hash: BytesN<32>,
+    ) -> Result<(), DistributionError> {
+        Self::require_upgrader(&env, &caller)?;
 
-    pub fn get_member_percent(env: Env, address: Address) -> Option<u32> {
-        env.storage()
-            .instance()
-            .get(&DataKey::MemberPercent(address))
-    }
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
 
-    pub fn get_admin(env: Env) -> Option<Address> {
-        env.storage().instance().get(&DataKey::Admin)
-    }
+        events::Upgraded { new_wasm_hash }.publish(&env);
 
-    pub fn get_token_contract(env: Env) -> Option<Address> {
-        env.storage().instance().get(&DataKey::TokenContract)
+        Ok(())
+    }
+
+    /// Convierte el almacenamiento al esquema de este código
+    /// Solo puede ser llamado por el admin o el contrato de gobernanza
+    ///
+    /// Aplica en orden cada paso pendiente desde la versión guardada. Es
+    /// idempotente: si el esquema ya está al día no modifica nada.
+    ///
+    /// # Retorna
+    /// La versión de esquema resultante
+    pub fn migrate(env: Env, caller: Address) -> Result<u32, DistributionError> {
+        Self::require_upgrader(&env, &caller)?;
+
+        let from_version = Self::get_schema_version(env.clone());
+        if from_version > SCHEMA_VERSION {
+            return Err(DistributionError::UnsupportedSchemaVersion);
+        }
+        if from_version == SCHEMA_VERSION {
+            return Ok(SCHEMA_VERSION);
+        }
+
+        if from_version < 2 {
+            Self::migrate_v1_to_v2(&env);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+        storage::extend_instance(&env);
+
+        events::Migrated {
+            from_version,
+            to_version: SCHEMA_VERSION,
+        }
+        .publish(&env);
+
+        Ok(SCHEMA_VERSION)
+    }
+
+    // ========================================================================
+    // Pausa de emergencia
+    // ========================================================================
+
+    /// Pausa el registro de generación (manual y por medidores) y los
+    /// cambios de membresía
+    /// Solo puede ser llamado por una cuenta con `PAUSER_ROLE`
+    ///
+    /// Las consultas, la privacidad y las liquidaciones siguen funcionando.
+    pub fn pause(env: Env, caller: Address) -> Result<(), DistributionError> {
+        Self::require_role(&env, &caller, &PAUSER_ROLE)?;
+
+        env.storage().instance().set(&DataKey::Paused, &true);
+
+        events::Paused { caller }.publish(&env);
+
+        Ok(())
+    }
+
+    /// Reanuda el contrato
+    /// Solo puede ser llamado por una cuenta con `PAUSER_ROLE`
+    pub fn unpause(env: Env, caller: Address) -> Result<(), DistributionError> {
+        Self::require_role(&env, &caller, &PAUSER_ROLE)?;
+
+        env.storage().instance().set(&DataKey::Paused, &false);
+
+        events::Unpaused { caller }.publish(&env);
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // Privacy Functions (commitments + Groth16)
+    // ========================================================================
+
+    /// Habilita el modo de privacidad
+    /// Solo puede ser llamado por una cuenta con `PRIVACY_ROLE` o por el
+    /// contrato de gobernanza
+    ///
+    /// # Con el modo habilitado:
+    /// - Se aceptan commitments (`record_private_consumption`, `commit_consumption`)
+    /// - La generación publica `GenerationTotalRecorded` (solo el total) en
+    ///   lugar del reparto por miembro de `GenerationRecorded`
+    /// - Las liquidaciones publican `SettlementClosed` sin la cantidad quemada
+    ///
+    /// # Limitaciones:
+    /// El modo solo cambia los eventos de este contrato. No oculta:
+    /// - Los eventos `mint`/`burn` del token HDROP (SEP-41), con la cantidad
+    ///   minteada o quemada a cada miembro, ni sus balances
+    /// - `get_member_allocated` / `get_member_allocations`: el almacenamiento
+    ///   de un contrato es público, restringir la consulta no lo oculta
+    pub fn enable_privacy(env: Env, caller: Address) -> Result<(), DistributionError> {
+        Self::set_privacy(&env, &caller, true)
+    }
+
+    /// Deshabilita el modo de privacidad
+    /// Solo puede ser llamado por una cuenta con `PRIVACY_ROLE` o por el
+    /// contrato de gobernanza
+    ///
+    /// Los commitments ya guardados se pueden seguir verificando y las
+    /// liquidaciones pendientes se pueden revelar o penalizar.
+    pub fn disable_privacy(env: Env, caller: Address) -> Result<(), DistributionError> {
+        Self::set_privacy(&env, &caller, false)
+    }
+
+    /// Registra consumo de forma privada usando un commitment
+    ///
+    /// # Privacidad:
+    /// En lugar de revelar la cantidad exacta consumida, el usuario
+    /// envía un "commitment" (hash) que prueba que consumió energía
+    /// sin revelar cuánto.
+    ///
+    /// # Argumentos
+    /// * `user` - Usuario que consumió energía
+    /// * `period` - Franja horaria del consumo (no puede ser futura)
+    /// * `commitment` - SHA256 de los datos en formato v1 (ver `privacy::encode_consumption_v1`)
+    ///
+    /// # Retorna
+    /// La posición del commitment en el historial del usuario para el período
+    ///
+    /// # Cómo funciona:
+    /// 1. Usuario genera commitment off-chain: SHA256 del formato v1, ligado a
+    ///    la red, este contrato, su `Address` y el período
+    /// 2. Envía commitment al contrato
+    /// 3. Contrato lo agrega al historial del período sin conocer la cantidad;
+    ///    los commitments anteriores nunca se sobrescriben
+    /// 4. Para demostrar que no superó lo asignado en el período, envía un
+    ///    proof Groth16 a `verify_consumption_proof` sin revelar la cantidad
+    pub fn record_private_consumption(
+        env: Env,
+        user: Address,
+        period: u64,
+        commitment: BytesN<32>,
+    ) -> Result<u32, DistributionError> {
+        user.require_auth();
+        Self::require_privacy(&env)?;
+
+        // Verificar que sea miembro
+        if !Self::is_member(env.clone(), user.clone()) {
+            return Err(DistributionError::NotAMember);
+        }
+
+        if period > env.ledger().timestamp() / PERIOD_SECONDS {
+            return Err(DistributionError::InvalidPeriod);
+        }
+
+        // Agregar al historial del período (append-only)
+        let count_key = DataKey::CommitmentCount(user.clone(), period);
+        let index: u32 = storage::get(&env, &count_key).unwrap_or(0);
+        storage::set(
+            &env,
+            &DataKey::CommitmentRecord(user.clone(), period, index),
+            &CommitmentRecord {
+                commitment: commitment.clone(),
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+        storage::set(&env, &count_key, &(index + 1));
+        storage::extend_instance(&env);
+
+        events::CommitmentStoredV2 {
+            user,
+            period,
+            index,
+            commitment,
+        }
+        .publish(&env);
+
+        Ok(index)
+    }
+
+    /// Verifica un commitment del historial revelando los datos
+    ///
+    /// Sirve para abrir un consumo pasado, por ejemplo en una disputa de
+    /// facturación. `user_data` revela el consumo: para demostrar
+    /// cumplimiento sin revelarlo se usa `verify_consumption_proof`.
+    ///
+    /// # Argumentos
+    /// * `user` - Usuario cuyo commitment se verifica
+    /// * `period` - Franja horaria del commitment
+    /// * `index` - Posición en el historial del período
+    /// * `opening` - kWh y secret del commitment (`V0` para commitments legacy)
+    pub fn verify_private_consumption(
+        env: Env,
+        user: Address,
+        period: u64,
+        index: u32,
+        opening: ConsumptionOpening,
+    ) -> bool {
+        // Obtener commitment almacenado
+        let stored: Option<CommitmentRecord> =
+            storage::get(&env, &DataKey::CommitmentRecord(user.clone(), period, index));
+
+        match stored {
+            Some(CommitmentRecord { commitment, .. }) => {
+                // Verificar usando el módulo de privacidad
+                privacy::verify_opening(&env, &commitment, &user, period, &opening)
+            }
+            None => false,
+        }
+    }
+
+    /// Verifica el commitment v0 que un usuario guardó antes del historial
+    /// por período (esquema 1), revelando los datos
+    ///
+    /// `migrate` conserva ese commitment, que no tiene período ni posición;
+    /// solo se abre con el formato `V0`.
+    ///
+    /// # Argumentos
+    /// * `user` - Usuario cuyo commitment legacy se verifica
+    /// * `opening` - Dirección, kWh y secret del commitment (`V0`)
+    pub fn verify_legacy_consumption(env: Env, user: Address, opening: ConsumptionOpening) -> bool {
+        if !matches!(opening, ConsumptionOpening::V0(..)) {
+            return false;
+        }
+
+        let stored: Option<BytesN<32>> = storage::get(&env, &DataKey::UserCommitment(user.clone()));
+        match stored {
+            Some(commitment) => privacy::verify_opening(&env, &commitment, &user, 0, &opening),
+            None => false,
+        }
+    }
+
+    /// Guarda el commitment Pedersen del consumo de un miembro en un período
+    /// y lo suma al agregado del período
+    ///
+    /// # Argumentos
+    /// * `user` - Miembro que consumió energía
+    /// * `period` - Franja horaria del consumo (no puede ser futura)
+    /// * `commitment` - `consumed_kwh·G + blinding·H` (ver `privacy::pedersen_commit`)
+    pub fn commit_consumption(
+        env: Env,
+        user: Address,
+        period: u64,
+        commitment: G1Affine,
+    ) -> Result<(), DistributionError> {
+        user.require_auth();
+        Self::require_privacy(&env)?;
+
+        if !Self::is_member(env.clone(), user.clone()) {
+            return Err(DistributionError::NotAMember);
+        }
+        if period > env.ledger().timestamp() / PERIOD_SECONDS {
+            return Err(DistributionError::InvalidPeriod);
+        }
+        if !privacy::is_valid_commitment(&env, &commitment) {
+            return Err(DistributionError::InvalidCommitment);
+        }
+
+        let key = DataKey::ConsumptionCommitment(user.clone(), period);
+        if storage::has(&env, &key) {
+            return Err(DistributionError::CommitmentAlreadyRecorded);
+        }
+        storage::set(
+            &env,
+            &key,
+            &ConsumptionCommitment {
+                commitment: commitment.clone(),
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+
+        let aggregate_key = DataKey::AggregateCommitment(period);
+        let aggregate = match storage::get::<AggregateCommitment>(&env, &aggregate_key) {
+            Some(aggregate) => AggregateCommitment {
+                commitment: privacy::add_commitments(&env, &aggregate.commitment, &commitment),
+                count: aggregate.count + 1,
+            },
+            None => AggregateCommitment {
+                commitment: commitment.clone(),
+                count: 1,
+            },
+        };
+        storage::set(&env, &aggregate_key, &aggregate);
+        storage::extend_instance(&env);
+
+        events::ConsumptionCommitted {
+            user,
+            period,
+            commitment,
+            aggregate: aggregate.commitment,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Verifica la apertura del consumo total de un período
+    ///
+    /// El auditor revela solo el total y la suma de los factores de cegado;
+    /// los consumos individuales siguen ocultos.
+    ///
+    /// # Retorna
+    /// `true` si `(total_kwh, blinding)` abre el agregado del período
+    pub fn open_aggregate(
+        env: Env,
+        period: u64,
+        total_kwh: i128,
+        blinding: Fr,
+    ) -> Result<bool, DistributionError> {
+        let aggregate: AggregateCommitment =
+            storage::get(&env, &DataKey::AggregateCommitment(period))
+                .ok_or(DistributionError::CommitmentNotFound)?;
+
+        Ok(privacy::open_commitment(&env, &aggregate.commitment, total_kwh, &blinding))
+    }
+
+    /// Commitment Pedersen de un miembro en un período
+    pub fn get_consumption_commitment(
+        env: Env,
+        user: Address,
+        period: u64,
+    ) -> Option<ConsumptionCommitment> {
+        storage::get(&env, &DataKey::ConsumptionCommitment(user, period))
+    }
+
+    /// Suma de los commitments Pedersen de un período
+    pub fn get_aggregate_commitment(env: Env, period: u64) -> Option<AggregateCommitment> {
+        storage::get(&env, &DataKey::AggregateCommitment(period))
+    }
+
+    /// Cantidad de commitments de un usuario en un período
+    pub fn get_commitment_count(env: Env, user: Address, period: u64) -> u32 {
+        storage::get(&env, &DataKey::CommitmentCount(user, period)).unwrap_or(0)
+    }
+
+    /// Página del historial de commitments de un usuario en un período
+    ///
+    /// # Argumentos
+    /// * `start` - Posición del primer commitment
+    /// * `limit` - Cantidad máxima a devolver (1 a `MAX_PAGE_SIZE`)
+    ///
+    /// # Retorna
+    /// Los commitments en orden de registro (vacío si `start` está fuera del historial)
+    pub fn get_commitment_history(
+        env: Env,
+        user: Address,
+        period: u64,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<CommitmentRecord>, DistributionError> {
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            return Err(DistributionError::InvalidRange);
+        }
+
+        let count = Self::get_commitment_count(env.clone(), user.clone(), period);
+        let end = count.min(start.saturating_add(limit));
+
+        let mut records = Vec::new(&env);
+        for index in start..end {
+            let key = DataKey::CommitmentRecord(user.clone(), period, index);
+            records.push_back(storage::get(&env, &key).unwrap());
+        }
+        Ok(records)
+    }
+
+    // ========================================================================
+    // Consumption Settlement (commit-reveal)
+    // ========================================================================
+
+    /// Compromete el consumo de un período para liquidarlo después
+    ///
+    /// # Argumentos
+    /// * `user` - Miembro que consumió energía
+    /// * `period` - Franja horaria del consumo (no puede ser futura)
+    /// * `commitment` - SHA256 de `encode_consumption_v1(.., user, period, kwh, secret)`
+    ///
+    /// # Flujo:
+    /// 1. `commit_settlement`: el miembro guarda el commitment
+    /// 2. `reveal_settlement`: antes de `deadline` revela kWh y secret, y se
+    ///    quema esa cantidad de HDROP de su balance
+    /// 3. `penalize_settlement`: vencido el plazo sin revelar, cualquiera puede
+    ///    quemar lo asignado al miembro en el período
+    pub fn commit_settlement(
+        env: Env,
+        user: Address,
+        period: u64,
+        commitment: BytesN<32>,
+    ) -> Result<(), DistributionError> {
+        user.require_auth();
+
+        if !Self::is_member(env.clone(), user.clone()) {
+            return Err(DistributionError::NotAMember);
+        }
+        let now = env.ledger().timestamp();
+        if period > now / PERIOD_SECONDS {
+            return Err(DistributionError::InvalidPeriod);
+        }
+        let deadline = (period + 1) * PERIOD_SECONDS + REVEAL_WINDOW;
+        if now > deadline {
+            return Err(DistributionError::RevealDeadlinePassed);
+        }
+
+        let key = DataKey::Settlement(user.clone(), period);
+        if storage::has(&env, &key) {
+            return Err(DistributionError::CommitmentAlreadyRecorded);
+        }
+        storage::set(
+            &env,
+            &key,
+            &Settlement {
+                commitment: commitment.clone(),
+                committed_at: now,
+                deadline,
+                status: SettlementStatus::Pending,
+            },
+        );
+        storage::extend_instance(&env);
+
+        events::SettlementCommitted {
+            user,
+            period,
+            commitment,
+            deadline,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Revela el consumo comprometido y quema esa cantidad de HDROP
+    ///
+    /// # Argumentos
+    /// * `user` - Miembro que comprometió el consumo
+    /// * `period` - Franja horaria de la liquidación
+    /// * `consumed_kwh` - Consumo a quemar (7 decimales, mayor que cero y
+    ///   hasta el balance de HDROP del miembro)
+    /// * `secret` - Secret usado en el commitment (formato v1)
+    pub fn reveal_settlement(
+        env: Env,
+        user: Address,
+        period: u64,
+        consumed_kwh: i128,
+        secret: BytesN<32>,
+    ) -> Result<(), DistributionError> {
+        user.require_auth();
+
+        if consumed_kwh <= 0 {
+            return Err(DistributionError::InvalidAmount);
+        }
+
+        let key = DataKey::Settlement(user.clone(), period);
+        let mut settlement: Settlement =
+            storage::get(&env, &key).ok_or(DistributionError::CommitmentNotFound)?;
+        if settlement.status != SettlementStatus::Pending {
+            return Err(DistributionError::SettlementClosed);
+        }
+        if env.ledger().timestamp() > settlement.deadline {
+            return Err(DistributionError::RevealDeadlinePassed);
+        }
+
+        // Solo formato v1: un commitment legacy no está ligado al usuario
+        let opening = ConsumptionOpening::V1(consumed_kwh, secret);
+        if !privacy::verify_opening(&env, &settlement.commitment, &user, period, &opening) {
+            return Err(DistributionError::InvalidReveal);
+        }
+
+        let token_contract: Address =
+            env.storage().instance().get(&DataKey::TokenContract).unwrap();
+        let balance = energy_token_interface::EnergyTokenClient::new(&env, &token_contract)
+            .balance(&user);
+        if consumed_kwh > balance {
+            return Err(DistributionError::InsufficientBalance);
+        }
+
+        settlement.status = SettlementStatus::Revealed(consumed_kwh);
+        storage::set(&env, &key, &settlement);
+        Self::burn_consumption(&env, &user, consumed_kwh);
+
+        Self::publish_settlement(&env, user, period, consumed_kwh, false);
+
+        Ok(())
+    }
+
+    /// Penaliza una liquidación que no se reveló antes del plazo
+    ///
+    /// Quema lo asignado al miembro en el período (o su balance, si es
+    /// menor), como si hubiera consumido todo. Cualquiera puede invocarla.
+    ///
+    /// # Retorna
+    /// La cantidad quemada
+    pub fn penalize_settlement(
+        env: Env,
+        user: Address,
+        period: u64,
+    ) -> Result<i128, DistributionError> {
+        let key = DataKey::Settlement(user.clone(), period);
+        let mut settlement: Settlement =
+            storage::get(&env, &key).ok_or(DistributionError::CommitmentNotFound)?;
+        if settlement.status != SettlementStatus::Pending {
+            return Err(DistributionError::SettlementClosed);
+        }
+        if env.ledger().timestamp() <= settlement.deadline {
+            return Err(DistributionError::RevealWindowOpen);
+        }
+
+        let token_contract: Address =
+            env.storage().instance().get(&DataKey::TokenContract).unwrap();
+        let balance = energy_token_interface::EnergyTokenClient::new(&env, &token_contract)
+            .balance(&user);
+        let allocated: i128 =
+            storage::get(&env, &DataKey::MemberAllocation(user.clone(), period)).unwrap_or(0);
+        let penalty = allocated.min(balance).max(0);
+
+        settlement.status = SettlementStatus::Penalized(penalty);
+        storage::set(&env, &key, &settlement);
+        Self::burn_consumption(&env, &user, penalty);
+
+        Self::publish_settlement(&env, user, period, penalty, true);
+
+        Ok(penalty)
+    }
+
+    /// Liquidación de consumo de un miembro en un período
+    pub fn get_settlement(env: Env, user: Address, period: u64) -> Option<Settlement> {
+        storage::get(&env, &DataKey::Settlement(user, period))
+    }
+
+    /// Configura la verification key Groth16 del circuito de consumo
+    /// Solo puede ser llamado por una cuenta con `PRIVACY_ROLE`
+    ///
+    /// # Argumentos
+    /// * `vk` - Verification key exportada del setup del circuito
+    ///   (`CONSUMPTION_PUBLIC_INPUTS` inputs públicos, ver `privacy`)
+    pub fn set_verification_key(
+        env: Env,
+        caller: Address,
+        vk: VerificationKey,
+    ) -> Result<(), DistributionError> {
+        Self::require_role(&env, &caller, &PRIVACY_ROLE)?;
+
+        if vk.ic.len() != privacy::CONSUMPTION_PUBLIC_INPUTS + 1 {
+            return Err(DistributionError::InvalidVerificationKey);
+        }
+
+        env.storage().instance().set(&DataKey::VerificationKey, &vk);
+        storage::extend_instance(&env);
+
+        events::VerificationKeySet {
+            public_inputs: privacy::CONSUMPTION_PUBLIC_INPUTS,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Verifica un proof Groth16 de que el consumo comprometido por `user`
+    /// en un período no supera lo que el contrato le asignó en ese período
+    ///
+    /// Los inputs públicos los arma el contrato con su propio registro
+    /// (la asignación del período, ver `get_member_allocations`) y el
+    /// commitment guardado en el historial, así el proof no puede apuntar a
+    /// otro total (como el histórico), a otro período ni a otro consumo.
+    ///
+    /// # Argumentos
+    /// * `user` - Miembro que demuestra cumplimiento
+    /// * `period` - Franja horaria del consumo
+    /// * `index` - Posición del commitment en el historial del período
+    /// * `proof` - Proof Groth16 generado off-chain
+    ///
+    /// # Retorna
+    /// `true` si el proof es válido
+    pub fn verify_consumption_proof(
+        env: Env,
+        user: Address,
+        period: u64,
+        index: u32,
+        proof: Groth16Proof,
+    ) -> Result<bool, DistributionError> {
+        let vk: VerificationKey = env
+            .storage()
+            .instance()
+            .get(&DataKey::VerificationKey)
+            .ok_or(DistributionError::VerificationKeyNotSet)?;
+
+        let record: CommitmentRecord =
+            storage::get(&env, &DataKey::CommitmentRecord(user.clone(), period, index))
+                .ok_or(DistributionError::CommitmentNotFound)?;
+
+        let allocated: i128 =
+            storage::get(&env, &DataKey::MemberAllocation(user, period)).unwrap_or(0);
+        let public_inputs =
+            privacy::consumption_public_inputs(&env, allocated, &record.commitment);
+
+        Ok(privacy::verify_groth16(&env, &vk, &proof, &public_inputs))
+    }
+
+    /// Verification key Groth16 configurada
+    pub fn get_verification_key(env: Env) -> Option<VerificationKey> {
+        env.storage().instance().get(&DataKey::VerificationKey)
+    }
+
+    // ========================================================================
+    // View Functions
+    // ========================================================================
+
+    pub fn is_member(env: Env, address: Address) -> bool {
+        storage::get(&env, &DataKey::Member(address)).unwrap_or(false)
+    }
+
+    /// Participación de un miembro en puntos básicos (10_000 = 100%)
+    ///
+    /// Los miembros guardados antes de `migrate` se leen desde el
+    /// porcentaje entero legacy.
+    pub fn get_member_share(env: Env, address: Address) -> Option<u32> {
+        if let Some(share_bps) = storage::get(&env, &DataKey::MemberShare(address.clone())) {
+            return Some(share_bps);
+        }
+
+        env.storage()
+            .instance()
+            .get::<_, u32>(&DataKey::MemberPercent(address))
+            .map(|percent| percent * 100)
+    }
+
+    /// Participación de un miembro en porcentaje entero (0-100)
+    ///
+    /// Se mantiene para los clientes anteriores a los puntos básicos: trunca
+    /// la participación (3_350 bps = 33%). Usar `get_member_share`.
+    pub fn get_member_percent(env: Env, address: Address) -> Option<u32> {
+        Self::get_member_share(env, address).map(|share_bps| share_bps / 100)
+    }
+
+    pub fn get_token_contract(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::TokenContract)
     }
 
     pub fn get_required_approvals(env: Env) -> Option<u32> {
         env.storage().instance().get(&DataKey::RequiredApprovals)
     }
 
-    pub fn are_members_initialized(env: Env) -> bool {
-        env.storage()
-            .instance()
-            .get(&DataKey::MembersInitialized)
-            .unwrap_or(false)
+    pub fn are_members_initialized(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::MembersInitialized)
+            .unwrap_or(false)
+    }
+
+    pub fn get_total_generated(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalGenerated)
+            .unwrap_or(0)
+    }
+
+    pub fn get_member_list(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::MemberList)
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    pub fn get_governance(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Governance)
+    }
+
+    pub fn is_paused(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false)
+    }
+
+    pub fn is_privacy_enabled(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::PrivacyEnabled)
+            .unwrap_or(false)
+    }
+
+    /// Versión del esquema de almacenamiento
+    ///
+    /// Los contratos desplegados antes de versionar el esquema no la tienen
+    /// guardada y se consideran versión 1.
+    pub fn get_schema_version(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::SchemaVersion)
+            .unwrap_or(1)
+    }
+
+    pub fn get_generation(env: Env, period: u64) -> Option<GenerationRecord> {
+        storage::get(&env, &DataKey::Generation(period))
+    }
+
+    /// Historial de generación entre dos períodos (inclusive)
+    ///
+    /// Solo devuelve los períodos con generación registrada, ordenados.
+    /// El rango puede cubrir como máximo `MAX_HISTORY_RANGE` períodos.
+    pub fn get_generation_history(
+        env: Env,
+        from_period: u64,
+        to_period: u64,
+    ) -> Result<Vec<GenerationRecord>, DistributionError> {
+        if from_period > to_period || to_period - from_period >= MAX_HISTORY_RANGE {
+            return Err(DistributionError::InvalidRange);
+        }
+
+        let mut history = Vec::new(&env);
+        for period in from_period..=to_period {
+            // Sin extender TTL: recorrer el rango no debería escribir en el ledger
+            if let Some(record) = env
+                .storage()
+                .persistent()
+                .get::<_, GenerationRecord>(&DataKey::Generation(period))
+            {
+                history.push_back(record);
+            }
+        }
+
+        Ok(history)
+    }
+
+    /// Total histórico de tokens asignados a un miembro por la distribución
+    ///
+    /// No incluye transferencias ni quemas: es lo que el miembro recibió por
+    /// su participación. Se conserva aunque el miembro deje la comunidad.
+    pub fn get_member_allocated(env: Env, address: Address) -> i128 {
+        storage::get(&env, &DataKey::MemberAllocated(address)).unwrap_or(0)
+    }
+
+    /// Asignaciones de un miembro entre dos períodos (inclusive)
+    ///
+    /// Solo devuelve los períodos en que el miembro recibió tokens.
+    /// El rango puede cubrir como máximo `MAX_HISTORY_RANGE` períodos.
+    pub fn get_member_allocations(
+        env: Env,
+        address: Address,
+        from_period: u64,
+        to_period: u64,
+    ) -> Result<Vec<PeriodAllocation>, DistributionError> {
+        if from_period > to_period || to_period - from_period >= MAX_HISTORY_RANGE {
+            return Err(DistributionError::InvalidRange);
+        }
+
+        let mut allocations = Vec::new(&env);
+        for period in from_period..=to_period {
+            if let Some(amount) = env
+                .storage()
+                .persistent()
+                .get::<_, i128>(&DataKey::MemberAllocation(address.clone(), period))
+            {
+                allocations.push_back(PeriodAllocation { period, amount });
+            }
+        }
+
+        Ok(allocations)
+    }
+
+    pub fn get_meter(env: Env, meter: BytesN<32>) -> Option<MeterState> {
+        storage::get(&env, &DataKey::Meter(meter))
+    }
+
+    pub fn get_pending_action(env: Env, action_id: u32) -> Option<PendingAction> {
+        storage::get(&env, &DataKey::PendingAction(action_id))
+    }
+
+    // ========================================================================
+    // Helpers
+    // ========================================================================
+
+    /// Verifica que `caller` firmó la invocación y es el admin o el
+    /// contrato de gobernanza
+    fn require_upgrader(env: &Env, caller: &Address) -> Result<(), DistributionError> {
+        caller.require_auth();
+
+        let is_admin = access_control::get_admin(env).as_ref() == Some(caller);
+        let is_governance = Self::get_governance(env.clone()).as_ref() == Some(caller);
+        if !is_admin && !is_governance {
+            return Err(DistributionError::NotAuthorized);
+        }
+
+        Ok(())
+    }
+
+    /// Esquema 1 → 2: mueve los datos de los miembros de `instance()` a
+    /// almacenamiento persistente y convierte los porcentajes legacy a
+    /// puntos básicos
+    ///
+    /// Los commitments de quienes ya no son miembros no se pueden enumerar
+    /// y quedan en `instance()`. Publica `SharesMigrated` si convirtió
+    /// porcentajes.
+    fn migrate_v1_to_v2(env: &Env) {
+        let instance = env.storage().instance();
+        let mut migrated = 0u32;
+
+        for member in Self::get_member_list(env.clone()).iter() {
+            for key in [
+                DataKey::Member(member.clone()),
+                DataKey::MemberShare(member.clone()),
+                DataKey::UserCommitment(member.clone()),
+            ] {
+                if let Some(value) = instance.get::<_, Val>(&key) {
+                    storage::set(env, &key, &value);
+                    instance.remove(&key);
+                }
+            }
+
+            let legacy_key = DataKey::MemberPercent(member.clone());
+            if let Some(percent) = instance.get::<_, u32>(&legacy_key) {
+                storage::set(env, &DataKey::MemberShare(member), &(percent * 100));
+                instance.remove(&legacy_key);
+                migrated += 1;
+            }
+        }
+
+        if migrated > 0 {
+            events::SharesMigrated { migrated }.publish(env);
+        }
+    }
+
+    /// Verifica que `caller` firmó la invocación y tiene el rol indicado
+    fn require_role(env: &Env, caller: &Address, role: &Symbol) -> Result<(), DistributionError> {
+        caller.require_auth();
+
+        if access_control::has_role(env, caller, role).is_none() {
+            return Err(DistributionError::NotAuthorized);
+        }
+
+        Ok(())
+    }
+
+    /// Cambia el modo de privacidad si `caller` tiene `PRIVACY_ROLE` o es
+    /// el contrato de gobernanza
+    fn set_privacy(env: &Env, caller: &Address, enabled: bool) -> Result<(), DistributionError> {
+        let is_governance = Self::get_governance(env.clone()).as_ref() == Some(caller);
+        if is_governance {
+            caller.require_auth();
+        } else {
+            Self::require_role(env, caller, &PRIVACY_ROLE)?;
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::PrivacyEnabled, &enabled);
+
+        events::PrivacyToggled { enabled }.publish(env);
+
+        Ok(())
+    }
+
+    /// Falla con `ContractPaused` si el contrato está pausado
+    fn require_not_paused(env: &Env) -> Result<(), DistributionError> {
+        if Self::is_paused(env.clone()) {
+            return Err(DistributionError::ContractPaused);
+        }
+        Ok(())
+    }
+
+    /// Falla con `PrivacyDisabled` si el modo de privacidad no está habilitado
+    fn require_privacy(env: &Env) -> Result<(), DistributionError> {
+        if !Self::is_privacy_enabled(env.clone()) {
+            return Err(DistributionError::PrivacyDisabled);
+        }
+        Ok(())
+    }
+
+    /// Publica el cierre de una liquidación; en modo privacidad sin la cantidad
+    fn publish_settlement(env: &Env, user: Address, period: u64, amount: i128, penalized: bool) {
+        if Self::is_privacy_enabled(env.clone()) {
+            events::SettlementClosed {
+                user,
+                period,
+                penalized,
+            }
+            .publish(env);
+        } else {
+            events::ConsumptionSettled {
+                user,
+                period,
+                amount,
+                penalized,
+            }
+            .publish(env);
+        }
+    }
+
+    /// Distribuye kWh generados entre los miembros y actualiza el historial
+    ///
+    /// 1. Calcula cuántos tokens le corresponden a cada miembro según su %
+    ///    (el remanente del redondeo va al mayor propietario, ver `split`)
+    /// 2. Verifica que el registro del período y el total no desborden
+    /// 3. Mintea tokens HoneyDrop a cada miembro
+    /// 4. Suma la generación al registro del período
+    /// 5. Actualiza el total generado
+    fn distribute(env: &Env, period: u64, kwh_generated: i128) -> Result<(), DistributionError> {
+        // Verificar que los miembros estén inicializados
+        let initialized: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::MembersInitialized)
+            .unwrap_or(false);
+
+        if !initialized {
+            return Err(DistributionError::MembersNotInitialized);
+        }
+
+        // Obtener el contrato del token
+        let token_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::TokenContract)
+            .unwrap();
+
+        // Obtener lista de miembros
+        let member_list: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::MemberList)
+            .unwrap();
+
+        // Crear cliente del token
+        let token_client = energy_token_interface::EnergyTokenClient::new(env, &token_contract);
+
+        // Obtener participaciones en el orden de la lista de miembros
+        let mut shares_bps: Vec<u32> = Vec::new(env);
+        for member in member_list.iter() {
+            shares_bps.push_back(Self::get_member_share(env.clone(), member).unwrap());
+        }
+
+        // Calcular tokens a mintear sin perder el remanente del redondeo
+        let shares = split::split_generation(env, kwh_generated, &shares_bps);
+
+        // Calcular los acumulados antes de mintear
+        let period_key = DataKey::Generation(period);
+        let mut record: GenerationRecord =
+            storage::get(env, &period_key).unwrap_or(GenerationRecord {
+                period,
+                kwh: 0,
+                recorded_at: 0,
+            });
+        record.kwh = record
+            .kwh
+            .checked_add(kwh_generated)
+            .ok_or(DistributionError::ArithmeticOverflow)?;
+        record.recorded_at = env.ledger().timestamp();
+
+        let total_generated = Self::get_total_generated(env.clone())
+            .checked_add(kwh_generated)
+            .ok_or(DistributionError::ArithmeticOverflow)?;
+
+        // Distribuir tokens a cada miembro según su porcentaje
+        for i in 0..member_list.len() {
+            let member = member_list.get(i).unwrap();
+            let tokens_to_mint = shares.get(i).unwrap();
+
+            if tokens_to_mint == 0 {
+                continue;
+            }
+
+            // Mintear tokens al miembro
+            // El contrato de distribución debe tener rol de MINTER en el token contract
+            token_client.mint_energy(&member, &tokens_to_mint, &env.current_contract_address());
+
+            // Registrar la asignación del miembro (total y por período)
+            Self::add_to(env, &DataKey::MemberAllocated(member.clone()), tokens_to_mint)?;
+            Self::add_to(env, &DataKey::MemberAllocation(member, period), tokens_to_mint)?;
+        }
+
+        // Sumar al registro del período
+        storage::set(env, &period_key, &record);
+
+        // Actualizar total generado
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalGenerated, &total_generated);
+        storage::extend_instance(env);
+
+        // En modo privacidad este contrato no publica el reparto por miembro
+        // (el evento `mint` del token sigue mostrando cada cantidad)
+        if Self::is_privacy_enabled(env.clone()) {
+            events::GenerationTotalRecorded {
+                period,
+                kwh: kwh_generated,
+            }
+            .publish(env);
+        } else {
+            events::GenerationRecorded {
+                period,
+                kwh: kwh_generated,
+                members: member_list,
+                amounts: shares,
+            }
+            .publish(env);
+        }
+
+        Ok(())
+    }
+
+    /// Quema HDROP de un miembro por consumo liquidado
+    /// El contrato de distribución debe tener `SETTLEMENT_ROLE` en el token contract
+    fn burn_consumption(env: &Env, user: &Address, amount: i128) {
+        if amount == 0 {
+            return;
+        }
+
+        let token_contract: Address =
+            env.storage().instance().get(&DataKey::TokenContract).unwrap();
+        energy_token_interface::EnergyTokenClient::new(env, &token_contract).burn_consumption(
+            user,
+            &amount,
+            &env.current_contract_address(),
+        );
+        storage::extend_instance(env);
+    }
+
+    /// Suma `amount` a un acumulador i128 en almacenamiento persistente
+    fn add_to(env: &Env, key: &DataKey, amount: i128) -> Result<(), DistributionError> {
+        let current: i128 = storage::get(env, key).unwrap_or(0);
+        let updated = current
+            .checked_add(amount)
+            .ok_or(DistributionError::ArithmeticOverflow)?;
+        storage::set(env, key, &updated);
+        Ok(())
+    }
+
+    /// Ejecuta la acción si tiene suficientes aprobaciones, o la guarda
+    ///
+    /// Solo cuentan las aprobaciones de quienes siguen siendo miembros, por
+    /// si la lista cambió mientras la acción estaba pendiente. Un umbral que
+    /// ya no se puede cumplir descarta la acción (`ActionDropped`) en vez de
+    /// dejarla pendiente.
+    fn execute_if_approved(env: &Env, pending: PendingAction) -> Result<bool, DistributionError> {
+        let required: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::RequiredApprovals)
+            .unwrap();
+
+        let mut valid_approvals = 0u32;
+        for approver in pending.approvals.iter() {
+            if Self::is_member(env.clone(), approver) {
+                valid_approvals += 1;
+            }
+        }
+
+        let key = DataKey::PendingAction(pending.id);
+        if valid_approvals < required {
+            storage::set(env, &key, &pending);
+            return Ok(false);
+        }
+
+        // La membresía pudo cambiar desde que se propuso: se valida antes de
+        // eliminar la acción, así un error no deshace la eliminación
+        if let MultisigAction::SetRequiredApprovals(required) = &pending.action {
+            if *required > Self::get_member_list(env.clone()).len() {
+                storage::remove(env, &key);
+                events::ActionDropped {
+                    action_id: pending.id,
+                }
+                .publish(env);
+                return Ok(false);
+            }
+        }
+
+        storage::remove(env, &key);
+
+        match pending.action {
+            MultisigAction::SetMembers(members, shares_bps) => {
+                Self::write_members(env, &members, &shares_bps)?;
+            }
+            MultisigAction::SetRequiredApprovals(required) => {
+                env.storage()
+                    .instance()
+                    .set(&DataKey::RequiredApprovals, &required);
+            }
+        }
+
+        events::ActionExecuted {
+            action_id: pending.id,
+        }
+        .publish(env);
+
+        Ok(true)
+    }
+
+    /// Valida y guarda la lista de miembros con sus participaciones
+    ///
+    /// Los miembros anteriores que no están en la nueva lista se eliminan,
+    /// para que `is_member` y `get_member_share` sigan siendo correctos.
+    fn write_members(
+        env: &Env,
+        members: &Vec<Address>,
+        shares_bps: &Vec<u32>,
+    ) -> Result<(), DistributionError> {
+        // Verificar que members y shares_bps tienen la misma longitud
+        if members.len() != shares_bps.len() {
+            return Err(DistributionError::MemberShareMismatch);
+        }
+
+        // Verificar que las participaciones suman 10_000 (100%); una suma
+        // que desborda u32 tampoco es válida
+        let total = shares_bps
+            .iter()
+            .try_fold(0u32, |total, share_bps| total.checked_add(share_bps));
+        if total != Some(TOTAL_SHARES_BPS) {
+            return Err(DistributionError::SharesMustSumTo10000);
+        }
+
+        // Con menos miembros que aprobaciones requeridas el multisig no
+        // podría aprobar nada más
+        let required: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::RequiredApprovals)
+            .unwrap();
+        if members.len() < required {
+            return Err(DistributionError::InvalidApprovalThreshold);
+        }
+
+        // Verificar que no hay direcciones repetidas
+        for i in 0..members.len() {
+            let member = members.get(i).unwrap();
+            if members.last_index_of(&member) != Some(i) {
+                return Err(DistributionError::DuplicateMember);
+            }
+        }
+
+        // Eliminar miembros anteriores que ya no forman parte de la comunidad
+        let old_members: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::MemberList)
+            .unwrap_or_else(|| Vec::new(env));
+
+        for old_member in old_members.iter() {
+            if !members.contains(&old_member) {
+                storage::remove(env, &DataKey::Member(old_member.clone()));
+                storage::remove(env, &DataKey::MemberShare(old_member.clone()));
+                env.storage()
+                    .instance()
+                    .remove(&DataKey::MemberPercent(old_member));
+            }
+        }
+
+        // Guardar miembros y sus participaciones
+        for i in 0..members.len() {
+            let member = members.get(i).unwrap();
+            let share_bps = shares_bps.get(i).unwrap();
+
+            storage::set(env, &DataKey::Member(member.clone()), &true);
+            storage::set(env, &DataKey::MemberShare(member.clone()), &share_bps);
+            env.storage()
+                .instance()
+                .remove(&DataKey::MemberPercent(member));
+        }
+
+        // Guardar lista de miembros
+        env.storage()
+            .instance()
+            .set(&DataKey::MemberList, members);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::MembersInitialized, &true);
+        storage::extend_instance(env);
+
+        events::MembersUpdated {
+            members: members.clone(),
+            shares_bps: shares_bps.clone(),
+        }
+        .publish(env);
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Implementaciones por defecto de OpenZeppelin
+// ============================================================================
+
+/// Implementa el sistema de control de acceso: roles y transferencia de
+/// admin en dos pasos (`transfer_admin_role` + `accept_admin_transfer`)
+#[default_impl]
+#[contractimpl]
+impl AccessControl for EnergyDistribution {}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use proptest::prelude::*;
+    use soroban_sdk::{
+        testutils::{storage::Persistent as _, Address as _, Events, Ledger},
+        vec, Env, Event as _, IntoVal, Symbol, Val,
+    };
+
+    #[test]
+    fn test_constructor() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let token_contract = Address::generate(&env);
+
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_contract, &3u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+
+        assert_eq!(client.get_admin(), Some(admin.clone()));
+        assert_eq!(client.get_token_contract(), Some(token_contract.clone()));
+        assert_eq!(client.get_required_approvals(), Some(3));
+        assert_eq!(client.are_members_initialized(), false);
+
+        // Nadie puede volver a inicializar el contrato para tomar el admin
+        let attacker = Address::generate(&env);
+        let args: Vec<Val> = vec![
+            &env,
+            attacker.into_val(&env),
+            attacker.into_val(&env),
+            1u32.into_val(&env),
+        ];
+        for name in ["__constructor", "initialize"] {
+            let result = env.try_invoke_contract::<(), soroban_sdk::Error>(
+                &contract_id,
+                &Symbol::new(&env, name),
+                args.clone(),
+            );
+            assert!(result.is_err());
+        }
+
+        assert_eq!(client.get_admin(), Some(admin));
+        assert_eq!(client.get_token_contract(), Some(token_contract));
+    }
+
+    #[test]
+    fn test_add_members_multisig_success() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let token_contract = Address::generate(&env);
+        let investor1 = Address::generate(&env);
+        let investor2 = Address::generate(&env);
+        let investor3 = Address::generate(&env);
+        let investor4 = Address::generate(&env);
+        let investor5 = Address::generate(&env);
+
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_contract, &3u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+
+        let approvers = vec![
+            &env,
+            investor1.clone(),
+            investor2.clone(),
+            investor3.clone(),
+        ];
+        let members = vec![
+            &env,
+            investor1.clone(),
+            investor2.clone(),
+            investor3.clone(),
+            investor4.clone(),
+            investor5.clone(),
+        ];
+        let shares_bps = vec![&env, 2_000, 3_000, 1_500, 2_500, 1_000];
+
+        let result = client.try_add_members_multisig(&approvers, &members, &shares_bps);
+        assert!(result.is_ok());
+
+        assert!(client.is_member(&investor1));
+        assert!(client.is_member(&investor2));
+        assert!(client.is_member(&investor3));
+        assert!(client.is_member(&investor4));
+        assert!(client.is_member(&investor5));
+
+        assert_eq!(client.get_member_share(&investor1), Some(2_000));
+        assert_eq!(client.get_member_share(&investor2), Some(3_000));
+        assert_eq!(client.get_member_share(&investor3), Some(1_500));
+        assert_eq!(client.get_member_share(&investor4), Some(2_500));
+        assert_eq!(client.get_member_share(&investor5), Some(1_000));
+        assert_eq!(client.get_member_percent(&investor3), Some(15));
+        assert_eq!(client.get_member_percent(&Address::generate(&env)), None);
+
+        assert!(client.are_members_initialized());
+
+        let member_list = client.get_member_list();
+        assert_eq!(member_list.len(), 5);
+    }
+
+    #[test]
+    fn test_update_members_by_governance() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let token_contract = Address::generate(&env);
+        let governance = Address::generate(&env);
+        let household1 = Address::generate(&env);
+        let household2 = Address::generate(&env);
+        let buyer = Address::generate(&env);
+
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_contract, &1u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+
+        client.add_members_multisig(
+            &vec![&env, household1.clone()],
+            &vec![&env, household1.clone(), household2.clone()],
+            &vec![&env, 6_000, 4_000],
+        );
+
+        // Sin gobernanza configurada no se puede redistribuir
+        let result = client.try_update_members(
+            &vec![&env, household1.clone(), buyer.clone()],
+            &vec![&env, 6_000, 4_000],
+        );
+        assert_eq!(result, Err(Ok(DistributionError::GovernanceNotSet)));
+
+        client.set_governance(&admin, &governance);
+        assert_eq!(client.get_governance(), Some(governance));
+
+        // household2 vende toda su parte a buyer
+        client.update_members(
+            &vec![&env, household1.clone(), buyer.clone()],
+            &vec![&env, 6_000, 4_000],
+        );
+
+        assert!(client.is_member(&household1));
+        assert!(client.is_member(&buyer));
+        assert_eq!(client.get_member_share(&buyer), Some(4_000));
+
+        // El miembro anterior ya no figura
+        assert!(!client.is_member(&household2));
+        assert_eq!(client.get_member_share(&household2), None);
+        assert_eq!(client.get_member_list().len(), 2);
+    }
+
+    #[test]
+    fn test_members_validation() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let token_contract = Address::generate(&env);
+        let investor1 = Address::generate(&env);
+        let investor2 = Address::generate(&env);
+
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_contract, &1u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+
+        let approvers = vec![&env, investor1.clone()];
+
+        let result = client.try_add_members_multisig(
+            &approvers,
+            &vec![&env, investor1.clone(), investor2.clone()],
+            &vec![&env, 10_000],
+        );
+        assert_eq!(result, Err(Ok(DistributionError::MemberShareMismatch)));
+
+        let result = client.try_add_members_multisig(
+            &approvers,
+            &vec![&env, investor1.clone(), investor2.clone()],
+            &vec![&env, 5_000, 4_000],
+        );
+        assert_eq!(result, Err(Ok(DistributionError::SharesMustSumTo10000)));
+
+        let result = client.try_add_members_multisig(
+            &approvers,
+            &vec![&env, investor1.clone(), investor1.clone()],
+            &vec![&env, 5_000, 5_000],
+        );
+        assert_eq!(result, Err(Ok(DistributionError::DuplicateMember)));
+    }
+
+    #[test]
+    fn test_member_changes_keep_threshold_reachable() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let (client, admin, members) = setup_community(&env);
+        let member = members.get(0).unwrap();
+        let governance = Address::generate(&env);
+        client.set_governance(&admin, &governance);
+
+        // Un solo miembro no puede reunir las 2 aprobaciones requeridas
+        let result = client.try_update_members(&vec![&env, member.clone()], &vec![&env, 10_000]);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidApprovalThreshold)));
+
+        let action = MultisigAction::SetMembers(vec![&env, member.clone()], vec![&env, 10_000]);
+        let action_id = client.propose_action(&member, &action, &3_600);
+        let result = client.try_approve_action(&members.get(1).unwrap(), &action_id);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidApprovalThreshold)));
+        assert_eq!(client.get_member_list(), members);
+
+        // Un umbral propuesto antes de achicar la comunidad se vuelve a validar:
+        // la última aprobación descarta la acción en vez de fallar
+        let action = MultisigAction::SetRequiredApprovals(3);
+        let raise_id = client.propose_action(&member, &action, &3_600);
+        client.update_members(
+            &vec![&env, member.clone(), members.get(1).unwrap()],
+            &vec![&env, 5_000, 5_000],
+        );
+        assert!(!client.approve_action(&members.get(1).unwrap(), &raise_id));
+        assert_eq!(
+            env.events().all(),
+            std::vec![
+                events::ActionApproved {
+                    action_id: raise_id,
+                    approver: members.get(1).unwrap(),
+                }
+                .to_xdr(&env, &client.address),
+                events::ActionDropped {
+                    action_id: raise_id,
+                }
+                .to_xdr(&env, &client.address),
+            ]
+        );
+        assert_eq!(client.get_pending_action(&raise_id), None);
+        assert_eq!(client.get_required_approvals(), Some(2));
+        let result = client.try_approve_action(&member, &raise_id);
+        assert_eq!(result, Err(Ok(DistributionError::ActionNotFound)));
+
+        // Una suma que desborda u32 no da la vuelta hasta 10_000
+        let result = client.try_update_members(
+            &vec![&env, member.clone(), members.get(1).unwrap()],
+            &vec![&env, u32::MAX, 10_001],
+        );
+        assert_eq!(result, Err(Ok(DistributionError::SharesMustSumTo10000)));
+    }
+
+    /// Comunidad de 3 hogares (50%/30%/20%) que requiere 2 aprobaciones
+    fn setup_community(env: &Env) -> (EnergyDistributionClient<'_>, Address, Vec<Address>) {
+        let admin = Address::generate(env);
+        let token_contract = Address::generate(env);
+        let members = vec![
+            env,
+            Address::generate(env),
+            Address::generate(env),
+            Address::generate(env),
+        ];
+
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_contract, &2u32));
+        let client = EnergyDistributionClient::new(env, &contract_id);
+
+        client.add_members_multisig(
+            &vec![env, members.get(0).unwrap(), members.get(1).unwrap()],
+            &members,
+            &vec![env, 5_000, 3_000, 2_000],
+        );
+
+        (client, admin, members)
+    }
+
+    #[test]
+    fn test_add_members_multisig_rejects_invalid_approvers() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let (client, _admin, members) = setup_community(&env);
+        let member1 = members.get(0).unwrap();
+        let outsider = Address::generate(&env);
+
+        // Aprobadores repetidos no cuentan dos veces
+        let result = client.try_add_members_multisig(
+            &vec![&env, member1.clone(), member1.clone()],
+            &members,
+            &vec![&env, 4_000, 4_000, 2_000],
+        );
+        assert_eq!(result, Err(Ok(DistributionError::DuplicateApprover)));
+
+        // Con la comunidad inicializada, los aprobadores deben ser miembros
+        let result = client.try_add_members_multisig(
+            &vec![&env, member1.clone(), outsider.clone()],
+            &members,
+            &vec![&env, 4_000, 4_000, 2_000],
+        );
+        assert_eq!(result, Err(Ok(DistributionError::NotAMember)));
+    }
+
+    #[test]
+    fn test_pending_action_approved_across_transactions() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let (client, _admin, members) = setup_community(&env);
+        let member1 = members.get(0).unwrap();
+        let member2 = members.get(1).unwrap();
+        let member3 = members.get(2).unwrap();
+
+        let new_shares = vec![&env, 4_000, 4_000, 2_000];
+        let id = client.propose_action(
+            &member1,
+            &MultisigAction::SetMembers(members.clone(), new_shares),
+            &(7 * 24 * 60 * 60),
+        );
+
+        // Solo la aprobación del proponente: todavía pendiente
+        let pending = client.get_pending_action(&id).unwrap();
+        assert_eq!(pending.approvals.len(), 1);
+        assert_eq!(client.get_member_share(&member1), Some(5_000));
+
+        // El proponente no puede aprobar dos veces
+        let result = client.try_approve_action(&member1, &id);
+        assert_eq!(result, Err(Ok(DistributionError::AlreadyApproved)));
+
+        // Un día después, otro miembro aprueba y se ejecuta
+        env.ledger().with_mut(|li| li.timestamp += 24 * 60 * 60);
+        assert!(client.approve_action(&member2, &id));
+
+        assert_eq!(client.get_member_share(&member1), Some(4_000));
+        assert_eq!(client.get_member_share(&member2), Some(4_000));
+        assert_eq!(client.get_pending_action(&id), None);
+
+        let result = client.try_approve_action(&member3, &id);
+        assert_eq!(result, Err(Ok(DistributionError::ActionNotFound)));
+    }
+
+    #[test]
+    fn test_pending_action_expiry_and_cancel() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let (client, admin, members) = setup_community(&env);
+        let member1 = members.get(0).unwrap();
+        let member2 = members.get(1).unwrap();
+        let outsider = Address::generate(&env);
+
+        let result = client.try_propose_action(
+            &outsider,
+            &MultisigAction::SetRequiredApprovals(3),
+            &60,
+        );
+        assert_eq!(result, Err(Ok(DistributionError::NotAMember)));
+
+        let result = client.try_propose_action(
+            &member1,
+            &MultisigAction::SetRequiredApprovals(3),
+            &(MAX_ACTION_DURATION + 1),
+        );
+        assert_eq!(result, Err(Ok(DistributionError::InvalidExpiry)));
+
+        let result = client.try_propose_action(
+            &member1,
+            &MultisigAction::SetRequiredApprovals(4),
+            &60,
+        );
+        assert_eq!(result, Err(Ok(DistributionError::InvalidApprovalThreshold)));
+
+        // Acción expirada
+        let id = client.propose_action(&member1, &MultisigAction::SetRequiredApprovals(3), &60);
+        env.ledger().with_mut(|li| li.timestamp += 60);
+        let result = client.try_approve_action(&member2, &id);
+        assert_eq!(result, Err(Ok(DistributionError::ActionExpired)));
+
+        // Solo el proponente o quien tenga MEMBERS_ROLE pueden cancelar
+        let id = client.propose_action(&member1, &MultisigAction::SetRequiredApprovals(3), &60);
+        let result = client.try_cancel_action(&member2, &id);
+        assert_eq!(result, Err(Ok(DistributionError::NotAuthorized)));
+
+        client.cancel_action(&admin, &id);
+        assert_eq!(client.get_pending_action(&id), None);
+        assert_eq!(client.get_required_approvals(), Some(2));
+    }
+
+    /// Token HDROP simulado: registra los minteos por dirección
+    #[contract]
+    pub struct MockToken;
+
+    #[contractimpl]
+    impl MockToken {
+        pub fn mint_energy(env: Env, to: Address, amount: i128, _minter: Address) {
+            let balance: i128 = env.storage().instance().get(&to).unwrap_or(0);
+            env.storage().instance().set(&to, &(balance + amount));
+        }
+
+        pub fn burn_consumption(env: Env, from: Address, amount: i128, _minter: Address) {
+            let balance: i128 = env.storage().instance().get(&from).unwrap_or(0);
+            assert!(balance >= amount, "insufficient balance");
+            env.storage().instance().set(&from, &(balance - amount));
+        }
+
+        pub fn balance(env: Env, id: Address) -> i128 {
+            env.storage().instance().get(&id).unwrap_or(0)
+        }
+    }
+
+    #[test]
+    fn test_record_generation_is_dust_free() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let token_id = env.register(MockToken, ());
+        let token = MockTokenClient::new(&env, &token_id);
+
+        let admin = Address::generate(&env);
+        let members = vec![
+            &env,
+            Address::generate(&env),
+            Address::generate(&env),
+            Address::generate(&env),
+        ];
+
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_id, &1u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+
+        client.add_members_multisig(
+            &vec![&env, members.get(0).unwrap()],
+            &members,
+            &vec![&env, 3_300, 3_400, 3_300],
+        );
+
+        // Lecturas que no se dividen exactamente por 100
+        let readings = [10i128, 1, 99, 12_3456789, 7_0000001];
+        env.ledger()
+            .with_mut(|li| li.timestamp = readings.len() as u64 * PERIOD_SECONDS);
+        let mut recorded = 0i128;
+        for (period, kwh) in readings.into_iter().enumerate() {
+            client.record_generation(&admin, &(period as u64), &kwh);
+            recorded += kwh;
+        }
+
+        let minted: i128 = members.iter().map(|m| token.balance(&m)).sum();
+        assert_eq!(minted, recorded);
+        assert_eq!(client.get_total_generated(), recorded);
+
+        // El registro por miembro coincide con lo minteado
+        for member in members.iter() {
+            assert_eq!(client.get_member_allocated(&member), token.balance(&member));
+        }
+    }
+
+    #[test]
+    fn test_record_generation_rejects_invalid_amounts() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 3 * PERIOD_SECONDS);
+
+        let token_id = env.register(MockToken, ());
+        let token = MockTokenClient::new(&env, &token_id);
+        let admin = Address::generate(&env);
+        let members = vec![&env, Address::generate(&env), Address::generate(&env)];
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_id, &1u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+        let shares_bps = vec![&env, 6_000, 4_000];
+        client.add_members_multisig(&vec![&env, admin.clone()], &members, &shares_bps);
+
+        for kwh in [0, -1, i128::MIN] {
+            let result = client.try_record_generation(&admin, &0, &kwh);
+            assert_eq!(result, Err(Ok(DistributionError::InvalidAmount)));
+        }
+        assert_eq!(client.get_generation(&0), None);
+        assert_eq!(client.get_total_generated(), 0);
+
+        // i128::MAX se reparte completo sin desbordar
+        client.record_generation(&admin, &0, &i128::MAX);
+        let minted: i128 = members.iter().map(|m| token.balance(&m)).sum();
+        assert_eq!(minted, i128::MAX);
+
+        // Cualquier generación adicional desbordaría el total
+        let result = client.try_record_generation(&admin, &1, &1);
+        assert_eq!(result, Err(Ok(DistributionError::ArithmeticOverflow)));
+        assert_eq!(client.get_total_generated(), i128::MAX);
+        assert_eq!(client.get_generation(&1), None);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_record_generation_accepts_only_positive_amounts(
+            kwh in prop_oneof![
+                Just(0i128),
+                Just(1i128),
+                Just(i128::MAX),
+                Just(i128::MIN),
+                any::<i128>(),
+            ],
+        ) {
+            let env = Env::default();
+            env.mock_all_auths();
+            env.ledger().with_mut(|li| li.timestamp = PERIOD_SECONDS);
+
+            let token_id = env.register(MockToken, ());
+            let admin = Address::generate(&env);
+            let members = vec![&env, Address::generate(&env), Address::generate(&env)];
+            let contract_id = env.register(EnergyDistribution, (&admin, &token_id, &1u32));
+            let client = EnergyDistributionClient::new(&env, &contract_id);
+            let shares_bps = vec![&env, 3_333, 6_667];
+            client.add_members_multisig(&vec![&env, admin.clone()], &members, &shares_bps);
+
+            let result = client.try_record_generation(&admin, &0, &kwh);
+            if kwh <= 0 {
+                prop_assert_eq!(result, Err(Ok(DistributionError::InvalidAmount)));
+                prop_assert_eq!(client.get_total_generated(), 0);
+            } else {
+                prop_assert_eq!(result, Ok(Ok(())));
+                prop_assert_eq!(client.get_total_generated(), kwh);
+                let allocated: i128 =
+                    members.iter().map(|m| client.get_member_allocated(&m)).sum();
+                prop_assert_eq!(allocated, kwh);
+            }
+        }
+    }
+
+    #[test]
+    fn test_migrate_legacy_percents_to_bps() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let token_contract = Address::generate(&env);
+        let household1 = Address::generate(&env);
+        let household2 = Address::generate(&env);
+
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_contract, &1u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+
+        // Estado guardado por la versión con porcentajes enteros (esquema 1)
+        env.as_contract(&contract_id, || {
+            let storage = env.storage().instance();
+            storage.remove(&DataKey::SchemaVersion);
+            storage.set(&DataKey::Member(household1.clone()), &true);
+            storage.set(&DataKey::Member(household2.clone()), &true);
+            storage.set(&DataKey::MemberPercent(household1.clone()), &70u32);
+            storage.set(&DataKey::MemberPercent(household2.clone()), &30u32);
+            storage.set(
+                &DataKey::MemberList,
+                &vec![&env, household1.clone(), household2.clone()],
+            );
+            storage.set(&DataKey::MembersInitialized, &true);
+        });
+
+        // Antes de migrar, los porcentajes legacy se leen como puntos básicos
+        assert_eq!(client.get_member_share(&household1), Some(7_000));
+
+        assert_eq!(client.migrate(&admin), SCHEMA_VERSION);
+        assert!(env
+            .events()
+            .all()
+            .events()
+            .contains(&events::SharesMigrated { migrated: 2 }.to_xdr(&env, &contract_id)));
+        assert_eq!(client.get_member_share(&household1), Some(7_000));
+        assert_eq!(client.get_member_share(&household2), Some(3_000));
+        assert_eq!(client.get_member_percent(&household1), Some(70));
+        assert!(client.is_member(&household1));
+        assert!(client.is_member(&household2));
+
+        // Idempotente
+        assert_eq!(client.migrate(&admin), SCHEMA_VERSION);
+        assert_eq!(client.get_member_share(&household1), Some(7_000));
+        env.as_contract(&contract_id, || {
+            assert!(!env
+                .storage()
+                .instance()
+                .has(&DataKey::MemberPercent(household1.clone())));
+        });
+    }
+
+    fn sign_reading(
+        env: &Env,
+        contract_id: &Address,
+        key: &SigningKey,
+        reading: &MeterReading,
+    ) -> BytesN<64> {
+        let message: std::vec::Vec<u8> = meter::reading_message(env, contract_id, reading)
+            .iter()
+            .collect();
+        BytesN::from_array(env, &key.sign(&message).to_bytes())
+    }
+
+    #[test]
+    fn test_signed_meter_readings_mint_delta() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 10_000);
+
+        let token_id = env.register(MockToken, ());
+        let token = MockTokenClient::new(&env, &token_id);
+
+        let admin = Address::generate(&env);
+        let household = Address::generate(&env);
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_id, &1u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+
+        client.add_members_multisig(
+            &vec![&env, household.clone()],
+            &vec![&env, household.clone()],
+            &vec![&env, 10_000],
+        );
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let meter = BytesN::from_array(&env, &key.verifying_key().to_bytes());
+        client.register_meter(&admin, &meter, &500_0000000);
+
+        let reading = MeterReading {
+            meter: meter.clone(),
+            interval_start: 0,
+            interval_end: 3_600,
+            cumulative_kwh: 512_5000000,
+        };
+        let signature = sign_reading(&env, &contract_id, &key, &reading);
+
+        // Solo se mintea la diferencia con la lectura de instalación
+        assert_eq!(client.submit_meter_reading(&reading, &signature), 12_5000000);
+        assert_eq!(token.balance(&household), 12_5000000);
+
+        // La misma lectura no puede enviarse dos veces
+        let result = client.try_submit_meter_reading(&reading, &signature);
+        assert_eq!(result, Err(Ok(DistributionError::StaleReading)));
+
+        // Un intervalo que se superpone con el anterior tampoco
+        let overlapping = MeterReading {
+            meter: meter.clone(),
+            interval_start: 1_800,
+            interval_end: 7_200,
+            cumulative_kwh: 520_0000000,
+        };
+        let signature = sign_reading(&env, &contract_id, &key, &overlapping);
+        let result = client.try_submit_meter_reading(&overlapping, &signature);
+        assert_eq!(result, Err(Ok(DistributionError::StaleReading)));
+
+        let next = MeterReading {
+            meter: meter.clone(),
+            interval_start: 3_600,
+            interval_end: 7_200,
+            cumulative_kwh: 520_0000000,
+        };
+        let signature = sign_reading(&env, &contract_id, &key, &next);
+        assert_eq!(client.submit_meter_reading(&next, &signature), 7_5000000);
+        assert_eq!(token.balance(&household), 20_0000000);
+        assert_eq!(client.get_total_generated(), 20_0000000);
+
+        // Ambas lecturas quedan en el historial por franja horaria
+        assert_eq!(client.get_generation(&0).unwrap().kwh, 12_5000000);
+        assert_eq!(client.get_generation(&1).unwrap().kwh, 7_5000000);
+
+        let state = client.get_meter(&meter).unwrap();
+        assert_eq!(state.last_interval_end, 7_200);
+        assert_eq!(state.last_cumulative_kwh, 520_0000000);
+    }
+
+    #[test]
+    fn test_period_has_a_single_generation_source() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 4 * PERIOD_SECONDS);
+
+        let token_id = env.register(MockToken, ());
+        let token = MockTokenClient::new(&env, &token_id);
+
+        let admin = Address::generate(&env);
+        let household = Address::generate(&env);
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_id, &1u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+
+        client.add_members_multisig(
+            &vec![&env, household.clone()],
+            &vec![&env, household.clone()],
+            &vec![&env, 10_000],
+        );
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let meter = BytesN::from_array(&env, &key.verifying_key().to_bytes());
+        client.register_meter(&admin, &meter, &100);
+        let reading = |start: u64, cumulative_kwh: i128| MeterReading {
+            meter: meter.clone(),
+            interval_start: start,
+            interval_end: start + PERIOD_SECONDS,
+            cumulative_kwh,
+        };
+
+        // Una lectura sin generación nueva no registra el período...
+        let idle = reading(0, 100);
+        let signature = sign_reading(&env, &contract_id, &key, &idle);
+        assert_eq!(client.submit_meter_reading(&idle, &signature), 0);
+        assert_eq!(client.get_generation(&0), None);
+        assert_eq!(client.get_meter(&meter).unwrap().last_interval_end, PERIOD_SECONDS);
+
+        // ... así que todavía se puede registrar a mano
+        client.record_generation(&admin, &0, &50);
+
+        // Un período registrado a mano no acepta lecturas
+        client.record_generation(&admin, &1, &50);
+        let late = reading(PERIOD_SECONDS, 130);
+        let signature = sign_reading(&env, &contract_id, &key, &late);
+        let result = client.try_submit_meter_reading(&late, &signature);
+        assert_eq!(result, Err(Ok(DistributionError::PeriodAlreadyRecorded)));
+
+        // Un período con lecturas no se puede registrar a mano
+        let metered = reading(2 * PERIOD_SECONDS, 130);
+        let signature = sign_reading(&env, &contract_id, &key, &metered);
+        assert_eq!(client.submit_meter_reading(&metered, &signature), 30);
+        let result = client.try_record_generation(&admin, &2, &50);
+        assert_eq!(result, Err(Ok(DistributionError::PeriodAlreadyRecorded)));
+
+        assert_eq!(client.get_generation(&1).unwrap().kwh, 50);
+        assert_eq!(client.get_generation(&2).unwrap().kwh, 30);
+        assert_eq!(token.balance(&household), 130);
     }
 
-    pub fn get_total_generated(env: Env) -> i128 {
-        env.storage()
-            .instance()
-            .get(&DataKey::TotalGenerated)
-            .unwrap_or(0)
+    #[test]
+    fn test_meter_reading_validation() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 10_000);
+
+
+        let admin = Address::generate(&env);
+        let token_contract = Address::generate(&env);
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_contract, &1u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let meter = BytesN::from_array(&env, &key.verifying_key().to_bytes());
+
+        let reading = MeterReading {
+            meter: meter.clone(),
+            interval_start: 0,
+            interval_end: 3_600,
+            cumulative_kwh: 10_0000000,
+        };
+        let signature = sign_reading(&env, &contract_id, &key, &reading);
+
+        // Medidor sin registrar
+        let result = client.try_submit_meter_reading(&reading, &signature);
+        assert_eq!(result, Err(Ok(DistributionError::MeterNotRegistered)));
+
+        client.register_meter(&admin, &meter, &20_0000000);
+        let result = client.try_register_meter(&admin, &meter, &0);
+        assert_eq!(result, Err(Ok(DistributionError::MeterAlreadyRegistered)));
+
+        // El acumulado no puede bajar
+        let result = client.try_submit_meter_reading(&reading, &signature);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidReading)));
+
+        // Intervalo en el futuro
+        let future = MeterReading {
+            meter: meter.clone(),
+            interval_start: 3_600,
+            interval_end: 20_000,
+            cumulative_kwh: 30_0000000,
+        };
+        let signature = sign_reading(&env, &contract_id, &key, &future);
+        let result = client.try_submit_meter_reading(&future, &signature);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidInterval)));
+
+        // Firma de otra clave
+        let valid = MeterReading {
+            meter: meter.clone(),
+            interval_start: 0,
+            interval_end: 3_600,
+            cumulative_kwh: 30_0000000,
+        };
+        let forger = SigningKey::from_bytes(&[9u8; 32]);
+        let forged = sign_reading(&env, &contract_id, &forger, &valid);
+        assert!(client.try_submit_meter_reading(&valid, &forged).is_err());
+
+        // Medidor desactivado
+        client.deactivate_meter(&admin, &meter);
+        let signature = sign_reading(&env, &contract_id, &key, &valid);
+        let result = client.try_submit_meter_reading(&valid, &signature);
+        assert_eq!(result, Err(Ok(DistributionError::MeterNotRegistered)));
     }
 
-    pub fn get_member_list(env: Env) -> Vec<Address> {
-        env.storage()
-            .instance()
-            .get(&DataKey::MemberList)
-            .unwrap_or_else(|| Vec::new(&env))
+    #[test]
+    fn test_generation_periods_are_idempotent_and_queryable() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 48 * PERIOD_SECONDS);
+
+        let token_id = env.register(MockToken, ());
+        let token = MockTokenClient::new(&env, &token_id);
+
+        let admin = Address::generate(&env);
+        let household = Address::generate(&env);
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_id, &1u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+
+        client.add_members_multisig(
+            &vec![&env, household.clone()],
+            &vec![&env, household.clone()],
+            &vec![&env, 10_000],
+        );
+
+        client.record_generation(&admin, &10, &5_0000000);
+        client.record_generation(&admin, &11, &7_0000000);
+        client.record_generation(&admin, &13, &2_0000000);
+
+        // La misma franja no se puede registrar dos veces
+        let result = client.try_record_generation(&admin, &11, &7_0000000);
+        assert_eq!(result, Err(Ok(DistributionError::PeriodAlreadyRecorded)));
+        assert_eq!(token.balance(&household), 14_0000000);
+
+        // Franjas que todavía no empezaron
+        let result = client.try_record_generation(&admin, &49, &1_0000000);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidPeriod)));
+
+        let record = client.get_generation(&11).unwrap();
+        assert_eq!(record.kwh, 7_0000000);
+        assert_eq!(record.recorded_at, 48 * PERIOD_SECONDS);
+        assert_eq!(client.get_generation(&12), None);
+
+        // Historial por rango: solo las franjas con generación
+        let history = client.get_generation_history(&0, &47);
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.get(0).unwrap().period, 10);
+        assert_eq!(history.get(2).unwrap().period, 13);
+
+        let history = client.get_generation_history(&11, &12);
+        assert_eq!(history.len(), 1);
+
+        let result = client.try_get_generation_history(&12, &11);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidRange)));
+        let result = client.try_get_generation_history(&0, &MAX_HISTORY_RANGE);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidRange)));
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use soroban_sdk::{testutils::Address as _, vec, Env};
+    #[test]
+    fn test_member_allocation_ledger() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 48 * PERIOD_SECONDS);
+
+        let token_id = env.register(MockToken, ());
+
+        let admin = Address::generate(&env);
+        let household1 = Address::generate(&env);
+        let household2 = Address::generate(&env);
+        let governance = Address::generate(&env);
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_id, &1u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+
+        client.set_governance(&admin, &governance);
+        client.add_members_multisig(
+            &vec![&env, household1.clone()],
+            &vec![&env, household1.clone(), household2.clone()],
+            &vec![&env, 7_500, 2_500],
+        );
+
+        client.record_generation(&admin, &20, &100_0000000);
+        client.record_generation(&admin, &21, &40_0000000);
+
+        // household2 deja la comunidad: su historial se conserva
+        client.update_members(&vec![&env, household1.clone()], &vec![&env, 10_000]);
+        client.record_generation(&admin, &22, &10_0000000);
+
+        assert_eq!(client.get_member_allocated(&household1), 115_0000000);
+        assert_eq!(client.get_member_allocated(&household2), 35_0000000);
+
+        let allocations = client.get_member_allocations(&household1, &20, &22);
+        assert_eq!(
+            allocations,
+            vec![
+                &env,
+                PeriodAllocation { period: 20, amount: 75_0000000 },
+                PeriodAllocation { period: 21, amount: 30_0000000 },
+                PeriodAllocation { period: 22, amount: 10_0000000 },
+            ]
+        );
+
+        let allocations = client.get_member_allocations(&household2, &21, &30);
+        assert_eq!(
+            allocations,
+            vec![&env, PeriodAllocation { period: 21, amount: 10_0000000 }]
+        );
+
+        let result = client.try_get_member_allocations(&household1, &22, &20);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidRange)));
+    }
 
     #[test]
-    fn test_initialize() {
+    fn test_state_changes_publish_events() {
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register(EnergyDistribution, ());
+        let token_id = env.register(MockToken, ());
+
+        let admin = Address::generate(&env);
+        let members = vec![&env, Address::generate(&env), Address::generate(&env)];
+        let shares_bps = vec![&env, 6_000u32, 4_000];
+
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_id, &1u32));
         let client = EnergyDistributionClient::new(&env, &contract_id);
 
+        // El constructor también publica los eventos de roles de stellar-access
+        assert_eq!(
+            env.events().all().events().last(),
+            Some(
+                &events::Initialized {
+                    admin: admin.clone(),
+                    token_contract: token_id.clone(),
+                    required_approvals: 1,
+                }
+                .to_xdr(&env, &contract_id)
+            )
+        );
+
+        client.add_members_multisig(&vec![&env, admin.clone()], &members, &shares_bps);
+        assert_eq!(
+            env.events().all(),
+            std::vec![events::MembersUpdated {
+                members: members.clone(),
+                shares_bps,
+            }
+            .to_xdr(&env, &contract_id)]
+        );
+
+        // La generación publica el reparto por miembro
+        env.ledger().with_mut(|li| li.timestamp = 5 * PERIOD_SECONDS);
+        client.record_generation(&admin, &4, &10);
+        assert_eq!(
+            env.events().all(),
+            std::vec![events::GenerationRecorded {
+                period: 4,
+                kwh: 10,
+                members: members.clone(),
+                amounts: vec![&env, 6, 4],
+            }
+            .to_xdr(&env, &contract_id)]
+        );
+
+        client.enable_privacy(&admin);
+        assert_eq!(
+            env.events().all(),
+            std::vec![events::PrivacyToggled { enabled: true }.to_xdr(&env, &contract_id)]
+        );
+
+        let user = members.get(0).unwrap();
+        let commitment = BytesN::from_array(&env, &[7u8; 32]);
+        client.record_private_consumption(&user, &0, &commitment);
+        assert_eq!(
+            env.events().all(),
+            std::vec![events::CommitmentStoredV2 {
+                user,
+                period: 0,
+                index: 0,
+                commitment,
+            }
+            .to_xdr(&env, &contract_id)]
+        );
+    }
+
+    #[test]
+    fn test_executed_action_publishes_events_in_order() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let (client, _admin, members) = setup_community(&env);
+        let proposer = members.get(0).unwrap();
+        let approver = members.get(1).unwrap();
+        let action = MultisigAction::SetRequiredApprovals(3);
+
+        let id = client.propose_action(&proposer, &action, &3_600);
+        assert_eq!(
+            env.events().all(),
+            std::vec![events::ActionProposed {
+                action_id: id,
+                proposer,
+                action,
+                expires_at: env.ledger().timestamp() + 3_600,
+            }
+            .to_xdr(&env, &client.address)]
+        );
+
+        client.approve_action(&approver, &id);
+        assert_eq!(
+            env.events().all(),
+            std::vec![
+                events::ActionApproved {
+                    action_id: id,
+                    approver,
+                }
+                .to_xdr(&env, &client.address),
+                events::ActionExecuted { action_id: id }.to_xdr(&env, &client.address),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_roles_and_two_step_admin_transfer() {
+        let env = Env::default();
+        env.mock_all_auths();
+
         let admin = Address::generate(&env);
         let token_contract = Address::generate(&env);
+        let reporter = Address::generate(&env);
+        let new_admin = Address::generate(&env);
 
-        let result = client.try_initialize(&admin, &token_contract, &3);
-        assert!(result.is_ok());
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_contract, &1u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
 
-        assert_eq!(client.get_admin(), Some(admin));
-        assert_eq!(client.get_token_contract(), Some(token_contract));
-        assert_eq!(client.get_required_approvals(), Some(3));
-        assert_eq!(client.are_members_initialized(), false);
+        // El admin inicial tiene todos los roles
+        for role in [REPORTER_ROLE, PRIVACY_ROLE, MEMBERS_ROLE, PAUSER_ROLE] {
+            assert!(client.has_role(&admin, &role).is_some());
+        }
+
+        // Sin rol no se puede reportar generación
+        let result = client.try_record_generation(&reporter, &0, &10);
+        assert_eq!(result, Err(Ok(DistributionError::NotAuthorized)));
+
+        // Con REPORTER_ROLE pasa el control de acceso (todavía no hay miembros)
+        client.grant_role(&admin, &reporter, &REPORTER_ROLE);
+        let result = client.try_record_generation(&reporter, &0, &10);
+        assert_eq!(result, Err(Ok(DistributionError::MembersNotInitialized)));
+
+        // Los roles son independientes
+        let result = client.try_enable_privacy(&reporter);
+        assert_eq!(result, Err(Ok(DistributionError::NotAuthorized)));
+        let result = client.try_set_governance(&reporter, &Address::generate(&env));
+        assert_eq!(result, Err(Ok(DistributionError::NotAuthorized)));
+
+        // Transferencia en dos pasos: el admin no cambia hasta que se acepta
+        client.transfer_admin_role(&new_admin, &(env.ledger().sequence() + 1_000));
+        assert_eq!(client.get_admin(), Some(admin.clone()));
+
+        client.accept_admin_transfer();
+        assert_eq!(client.get_admin(), Some(new_admin.clone()));
+
+        // El nuevo admin rota la clave comprometida del operador anterior
+        client.revoke_role(&new_admin, &admin, &REPORTER_ROLE);
+        let result = client.try_record_generation(&admin, &0, &10);
+        assert_eq!(result, Err(Ok(DistributionError::NotAuthorized)));
+    }
+
+    /// Avanza el ledger en pasos menores al TTL de la instancia,
+    /// manteniendo viva solo la instancia del contrato
+    fn advance_ledgers(env: &Env, contract_id: &Address, ledgers: u32) {
+        let step = storage::INSTANCE_TTL_EXTEND_TO - 1;
+        let mut remaining = ledgers;
+        while remaining > 0 {
+            let delta = remaining.min(step);
+            env.ledger().with_mut(|li| li.sequence_number += delta);
+            env.as_contract(contract_id, || storage::extend_instance(env));
+            remaining -= delta;
+        }
     }
 
     #[test]
-    fn test_add_members_multisig_success() {
+    fn test_member_data_ttl_extension() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let (client, admin, members) = setup_community(&env);
+        client.enable_privacy(&admin);
+        let member = members.get(0).unwrap();
+        let commitment = BytesN::from_array(&env, &[1u8; 32]);
+        client.record_private_consumption(&member, &0, &commitment);
+
+        let ttl = |key: &DataKey| {
+            env.as_contract(&client.address, || env.storage().persistent().get_ttl(key))
+        };
+        let member_key = DataKey::Member(member.clone());
+        let commitment_key = DataKey::CommitmentRecord(member.clone(), 0, 0);
+        assert_eq!(ttl(&member_key), storage::PERSISTENT_TTL_EXTEND_TO);
+        assert_eq!(ttl(&commitment_key), storage::PERSISTENT_TTL_EXTEND_TO);
+
+        // Leer una entrada cerca del umbral la extiende
+        advance_ledgers(&env, &client.address, 100 * storage::DAY_IN_LEDGERS);
+        assert_eq!(ttl(&member_key), 20 * storage::DAY_IN_LEDGERS);
+        assert!(client.is_member(&member));
+        assert_eq!(ttl(&member_key), storage::PERSISTENT_TTL_EXTEND_TO);
+
+        // Una entrada que no se lee no se extiende
+        assert_eq!(ttl(&commitment_key), 20 * storage::DAY_IN_LEDGERS);
+    }
+
+    #[test]
+    fn test_bump_storage_keeps_idle_members_alive() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let (client, admin, members) = setup_community(&env);
+        client.enable_privacy(&admin);
+        let member = members.get(0).unwrap();
+        let commitment = BytesN::from_array(&env, &[1u8; 32]);
+        client.record_private_consumption(&member, &0, &commitment);
+        let legacy = BytesN::from_array(&env, &[2u8; 32]);
+        env.as_contract(&client.address, || {
+            storage::set(&env, &DataKey::UserCommitment(member.clone()), &legacy);
+        });
+
+        // Registros de un período (el valor no importa para el TTL)
+        let period_keys = [
+            DataKey::Generation(0),
+            DataKey::MemberAllocation(member.clone(), 0),
+            DataKey::Settlement(member.clone(), 0),
+        ];
+        env.as_contract(&client.address, || {
+            for key in period_keys.iter() {
+                storage::set(&env, key, &true);
+            }
+        });
+
+        let result = client.try_bump_storage(&1, &0);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidRange)));
+        let result = client.try_bump_storage(&0, &MAX_HISTORY_RANGE);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidRange)));
+
+        // Un año sin actividad de los miembros, con mantenimiento mensual
+        let month = storage::INSTANCE_TTL_EXTEND_TO - 1;
+        for _ in 0..12 {
+            env.ledger().with_mut(|li| li.sequence_number += month);
+            // 3 miembros × (Member + MemberShare) + UserCommitment
+            // + período 0: 3 registros + CommitmentCount + CommitmentRecord
+            assert_eq!(client.bump_storage(&0, &1), 12);
+        }
+        assert!(12 * month > storage::PERSISTENT_TTL_EXTEND_TO);
+
+        // Ninguna entrada de los miembros llegó a archivarse
+        env.as_contract(&client.address, || {
+            for m in members.iter() {
+                for key in [DataKey::Member(m.clone()), DataKey::MemberShare(m)] {
+                    let ttl = env.storage().persistent().get_ttl(&key);
+                    assert!(ttl >= storage::PERSISTENT_TTL_THRESHOLD);
+                }
+            }
+            let key = DataKey::UserCommitment(member.clone());
+            let ttl = env.storage().persistent().get_ttl(&key);
+            assert!(ttl >= storage::PERSISTENT_TTL_THRESHOLD);
+
+            // Ni los registros del período
+            let history_keys = [
+                DataKey::CommitmentCount(member.clone(), 0),
+                DataKey::CommitmentRecord(member.clone(), 0, 0),
+            ];
+            for key in period_keys.iter().chain(history_keys.iter()) {
+                let ttl = env.storage().persistent().get_ttl(key);
+                assert!(ttl >= storage::PERSISTENT_TTL_THRESHOLD);
+            }
+        });
+
+        // Los datos siguen accesibles mucho después del TTL original
+        for m in members.iter() {
+            assert!(client.is_member(&m));
+        }
+        assert_eq!(client.get_member_share(&member), Some(5_000));
+        env.as_contract(&client.address, || {
+            let stored: Option<BytesN<32>> =
+                storage::get(&env, &DataKey::UserCommitment(member.clone()));
+            assert_eq!(stored, Some(legacy.clone()));
+        });
+        let history = client.get_commitment_history(&member, &0, &0, &1);
+        assert_eq!(history.get(0).unwrap().commitment, commitment);
+    }
+
+    #[test]
+    fn test_upgrade_is_admin_or_governance_only() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let (client, admin, members) = setup_community(&env);
+        let governance = Address::generate(&env);
+        let wasm_hash = BytesN::from_array(&env, &[0u8; 32]);
+
+        let result = client.try_upgrade(&members.get(0).unwrap(), &wasm_hash);
+        assert_eq!(result, Err(Ok(DistributionError::NotAuthorized)));
+        let result = client.try_migrate(&governance);
+        assert_eq!(result, Err(Ok(DistributionError::NotAuthorized)));
+
+        // El admin pasa el control de acceso; el hash no corresponde a un WASM subido
+        let result = client.try_upgrade(&admin, &wasm_hash);
+        assert!(matches!(result, Err(Err(_))));
+
+        client.set_governance(&admin, &governance);
+        assert_eq!(client.migrate(&governance), SCHEMA_VERSION);
+    }
+
+    /// WASM del código actual, la versión a la que se actualiza en
+    /// `test_upgrade_to_wasm_and_migrate`
+    ///
+    /// Solo con la feature `upgrade-tests`: hay que compilarlo antes
+    /// (`stellar contract build`) para no probar un WASM viejo.
+    #[cfg(feature = "upgrade-tests")]
+    const WASM: &[u8] =
+        include_bytes!("../../../target/wasm32v1-none/release/energy_distribution.wasm");
+
+    #[cfg(feature = "upgrade-tests")]
+    #[test]
+    fn test_upgrade_to_wasm_and_migrate() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let (client, admin, members) = setup_community(&env);
+        let member = members.get(0).unwrap();
+
+        // Estado guardado por la versión 1: datos por miembro en instance()
+        // y sin versión de esquema
+        env.as_contract(&client.address, || {
+            let instance = env.storage().instance();
+            instance.remove(&DataKey::SchemaVersion);
+            for (m, percent) in members.iter().zip([50u32, 30, 20]) {
+                storage::remove(&env, &DataKey::Member(m.clone()));
+                storage::remove(&env, &DataKey::MemberShare(m.clone()));
+                instance.set(&DataKey::Member(m.clone()), &true);
+                instance.set(&DataKey::MemberPercent(m), &percent);
+            }
+        });
+
+        let new_wasm_hash = env.deployer().upload_contract_wasm(WASM);
+        client.upgrade(&admin, &new_wasm_hash);
+        assert_eq!(
+            env.events().all(),
+            std::vec![events::Upgraded { new_wasm_hash }.to_xdr(&env, &client.address)]
+        );
+
+        // Desde aquí el contrato ejecuta el WASM
+        assert_eq!(client.migrate(&admin), SCHEMA_VERSION);
+        assert_eq!(
+            env.events().all(),
+            std::vec![
+                events::SharesMigrated { migrated: 3 }.to_xdr(&env, &client.address),
+                events::Migrated {
+                    from_version: 1,
+                    to_version: SCHEMA_VERSION,
+                }
+                .to_xdr(&env, &client.address),
+            ]
+        );
+        assert_eq!(client.get_schema_version(), SCHEMA_VERSION);
+
+        assert_eq!(client.get_member_list(), members);
+        for (m, share_bps) in members.iter().zip([5_000u32, 3_000, 2_000]) {
+            assert!(client.is_member(&m));
+            assert_eq!(client.get_member_share(&m), Some(share_bps));
+        }
+        assert_eq!(client.get_required_approvals(), Some(2));
+        assert!(client.has_role(&admin, &REPORTER_ROLE).is_some());
+
+        // El código nuevo sigue ejecutando acciones del multisig
+        let action = MultisigAction::SetRequiredApprovals(1);
+        let action_id = client.propose_action(&member, &action, &3_600);
+        client.approve_action(&members.get(1).unwrap(), &action_id);
+        assert_eq!(client.get_required_approvals(), Some(1));
+    }
+
+    #[test]
+    fn test_migrate_from_schema_v1() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let (client, admin, members) = setup_community(&env);
+        let member = members.get(0).unwrap();
+        let address_bytes = BytesN::from_array(&env, &[1u8; 32]);
+        let secret = BytesN::from_array(&env, &[9u8; 32]);
+        let legacy_data = privacy::hash_consumption_data(&env, &address_bytes, 7, &secret);
+        let commitment = privacy::generate_commitment(&env, &legacy_data);
+        assert_eq!(client.get_schema_version(), SCHEMA_VERSION);
+
+        // Estado guardado por la versión 1: datos por miembro en instance()
+        // y sin versión de esquema
+        env.as_contract(&client.address, || {
+            let instance = env.storage().instance();
+            instance.remove(&DataKey::SchemaVersion);
+            for (m, percent) in members.iter().zip([50u32, 30, 20]) {
+                storage::remove(&env, &DataKey::Member(m.clone()));
+                storage::remove(&env, &DataKey::MemberShare(m.clone()));
+                instance.set(&DataKey::Member(m.clone()), &true);
+                instance.set(&DataKey::MemberPercent(m), &percent);
+            }
+            instance.set(&DataKey::UserCommitment(member.clone()), &commitment);
+        });
+        assert_eq!(client.get_schema_version(), 1);
+        assert!(!client.is_member(&member));
+
+        assert_eq!(client.migrate(&admin), SCHEMA_VERSION);
+        assert_eq!(client.get_schema_version(), SCHEMA_VERSION);
+        for (m, share_bps) in members.iter().zip([5_000u32, 3_000, 2_000]) {
+            assert!(client.is_member(&m));
+            assert_eq!(client.get_member_share(&m), Some(share_bps));
+        }
+        env.as_contract(&client.address, || {
+            let instance = env.storage().instance();
+            assert!(!instance.has(&DataKey::Member(member.clone())));
+            assert!(!instance.has(&DataKey::MemberPercent(member.clone())));
+            assert!(!instance.has(&DataKey::UserCommitment(member.clone())));
+            let stored: Option<BytesN<32>> =
+                storage::get(&env, &DataKey::UserCommitment(member.clone()));
+            assert_eq!(stored, Some(commitment.clone()));
+        });
+
+        // El commitment legacy se sigue abriendo después de migrar
+        let opening = ConsumptionOpening::V0(address_bytes.clone(), 7, secret.clone());
+        assert!(client.verify_legacy_consumption(&member, &opening));
+        let opening = ConsumptionOpening::V0(address_bytes, 8, secret.clone());
+        assert!(!client.verify_legacy_consumption(&member, &opening));
+        let opening = ConsumptionOpening::V1(7, secret.clone());
+        assert!(!client.verify_legacy_consumption(&member, &opening));
+        assert_eq!(client.get_commitment_count(&member, &0), 0);
+
+        // Idempotente
+        assert_eq!(client.migrate(&admin), SCHEMA_VERSION);
+
+        // Un esquema más nuevo que el código no se puede migrar hacia atrás
+        env.as_contract(&client.address, || {
+            env.storage()
+                .instance()
+                .set(&DataKey::SchemaVersion, &(SCHEMA_VERSION + 1));
+        });
+        let result = client.try_migrate(&admin);
+        assert_eq!(result, Err(Ok(DistributionError::UnsupportedSchemaVersion)));
+    }
+
+    #[test]
+    fn test_consumption_proof_against_period_allocation() {
         let env = Env::default();
         env.mock_all_auths();
+        // Varios pairings superan el presupuesto por defecto
+        env.cost_estimate().budget().reset_unlimited();
+        env.ledger().with_mut(|li| li.timestamp = 48 * PERIOD_SECONDS);
 
-        let contract_id = env.register(EnergyDistribution, ());
+        let token_id = env.register(MockToken, ());
+        let admin = Address::generate(&env);
+        let member = Address::generate(&env);
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_id, &1u32));
         let client = EnergyDistributionClient::new(&env, &contract_id);
 
+        client.add_members_multisig(
+            &vec![&env, member.clone()],
+            &vec![&env, member.clone()],
+            &vec![&env, 10_000],
+        );
+        client.record_generation(&admin, &20, &100_0000000);
+        client.record_generation(&admin, &21, &10_0000000);
+        client.enable_privacy(&admin);
+
+        // 50 kWh en el período 21: entra en el histórico (110), no en el período (10)
+        let commitment = BytesN::from_array(&env, &[5u8; 32]);
+        let inputs = privacy::consumption_public_inputs(&env, 10_0000000, &commitment);
+        let (vk, proof) = privacy::tests::simulated_proof(&env, &inputs);
+        let lifetime_inputs = privacy::consumption_public_inputs(&env, 110_0000000, &commitment);
+        let (_, lifetime_proof) = privacy::tests::simulated_proof(&env, &lifetime_inputs);
+
+        let result = client.try_verify_consumption_proof(&member, &21, &0, &proof);
+        assert_eq!(result, Err(Ok(DistributionError::VerificationKeyNotSet)));
+
+        // Solo PRIVACY_ROLE configura la key, y debe tener 3 inputs públicos
+        let result = client.try_set_verification_key(&member, &vk);
+        assert_eq!(result, Err(Ok(DistributionError::NotAuthorized)));
+        let mut bad_vk = vk.clone();
+        bad_vk.ic.pop_back();
+        let result = client.try_set_verification_key(&admin, &bad_vk);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidVerificationKey)));
+
+        client.set_verification_key(&admin, &vk);
+        assert_eq!(client.get_verification_key(), Some(vk));
+
+        let result = client.try_verify_consumption_proof(&member, &21, &0, &proof);
+        assert_eq!(result, Err(Ok(DistributionError::CommitmentNotFound)));
+
+        client.record_private_consumption(&member, &21, &commitment);
+        assert!(client.verify_consumption_proof(&member, &21, &0, &proof));
+
+        // Un proof contra el total histórico no demuestra nada del período
+        assert!(!client.verify_consumption_proof(&member, &21, &0, &lifetime_proof));
+
+        // El proof queda atado al commitment de esa posición del historial...
+        let other = BytesN::from_array(&env, &[6u8; 32]);
+        client.record_private_consumption(&member, &21, &other);
+        assert!(!client.verify_consumption_proof(&member, &21, &1, &proof));
+        assert!(client.verify_consumption_proof(&member, &21, &0, &proof));
+
+        // ...y al período: el mismo commitment en otro período usa otra asignación
+        client.record_private_consumption(&member, &20, &commitment);
+        assert!(!client.verify_consumption_proof(&member, &20, &0, &proof));
+        let result = client.try_verify_consumption_proof(&member, &22, &0, &proof);
+        assert_eq!(result, Err(Ok(DistributionError::CommitmentNotFound)));
+    }
+
+    #[test]
+    fn test_pedersen_aggregate_opens_only_the_total() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 48 * PERIOD_SECONDS);
+
+        let (client, admin, members) = setup_community(&env);
+        client.enable_privacy(&admin);
+        let fr = |n: u32| Fr::from_u256(soroban_sdk::U256::from_u32(&env, n));
+
+        // Factores de cegado que suman cero: el total se abre con r = 0
+        let bls = env.crypto().bls12_381();
+        let r1 = fr(1_000);
+        let r2 = fr(2_000);
+        let r3 = bls.fr_sub(&fr(0), &bls.fr_add(&r1, &r2));
+        let consumed = [12_0000000i128, 7_5000000, 3_0000000];
+
+        for (i, r) in [r1, r2, r3].iter().enumerate() {
+            let member = members.get(i as u32).unwrap();
+            let commitment = privacy::pedersen_commit(&env, consumed[i], r);
+            client.commit_consumption(&member, &47, &commitment);
+            assert_eq!(
+                client.get_consumption_commitment(&member, &47),
+                Some(ConsumptionCommitment {
+                    commitment,
+                    timestamp: 48 * PERIOD_SECONDS,
+                })
+            );
+        }
+
+        assert_eq!(client.get_aggregate_commitment(&47).unwrap().count, 3);
+        assert!(client.open_aggregate(&47, &22_5000000, &fr(0)));
+        assert!(!client.open_aggregate(&47, &22_0000000, &fr(0)));
+
+        // Un commitment por miembro y período, solo miembros, sin períodos futuros
+        let member1 = members.get(0).unwrap();
+        let commitment = privacy::pedersen_commit(&env, 1, &fr(5));
+        let result = client.try_commit_consumption(&member1, &47, &commitment);
+        assert_eq!(result, Err(Ok(DistributionError::CommitmentAlreadyRecorded)));
+        let result = client.try_commit_consumption(&Address::generate(&env), &47, &commitment);
+        assert_eq!(result, Err(Ok(DistributionError::NotAMember)));
+        let result = client.try_commit_consumption(&member1, &49, &commitment);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidPeriod)));
+
+        let result = client.try_open_aggregate(&46, &0, &fr(0));
+        assert_eq!(result, Err(Ok(DistributionError::CommitmentNotFound)));
+    }
+
+    /// Commitment v1 de `user` en `period` para el contrato `contract`
+    fn v1_commitment(
+        env: &Env,
+        contract: &Address,
+        user: &Address,
+        period: u64,
+        kwh: i128,
+        secret: &BytesN<32>,
+    ) -> BytesN<32> {
+        let network_id = env.ledger().network_id();
+        let data =
+            privacy::encode_consumption_v1(env, &network_id, contract, user, period, kwh, secret);
+        privacy::generate_commitment(env, &data)
+    }
+
+    #[test]
+    fn test_commitment_history_is_append_only_and_paginated() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 48 * PERIOD_SECONDS);
+
+        let (client, admin, members) = setup_community(&env);
+        client.enable_privacy(&admin);
+        let member = members.get(0).unwrap();
+        let secret = BytesN::from_array(&env, &[2u8; 32]);
+
+        // Tres commitments en el mismo período
+        let kwh = [10_0000000i128, 4_0000000, 6_5000000];
+        let mut commitments = std::vec::Vec::new();
+        for (i, kwh) in kwh.iter().enumerate() {
+            let commitment = v1_commitment(&env, &client.address, &member, 47, *kwh, &secret);
+            env.ledger().with_mut(|li| li.timestamp += 60);
+            assert_eq!(client.record_private_consumption(&member, &47, &commitment), i as u32);
+            commitments.push(commitment);
+        }
+
+        // Y uno en formato v0 (legacy) en el siguiente
+        let address_bytes = BytesN::from_array(&env, &[1u8; 32]);
+        let legacy_data = privacy::hash_consumption_data(&env, &address_bytes, 3, &secret);
+        let legacy = privacy::generate_commitment(&env, &legacy_data);
+        client.record_private_consumption(&member, &48, &legacy);
+
+        assert_eq!(client.get_commitment_count(&member, &47), 3);
+        assert_eq!(client.get_commitment_count(&member, &48), 1);
+        assert_eq!(client.get_commitment_count(&members.get(1).unwrap(), &47), 0);
+
+        // Los commitments anteriores siguen verificables
+        for (i, kwh) in kwh.iter().enumerate() {
+            let opening = ConsumptionOpening::V1(*kwh, secret.clone());
+            assert!(client.verify_private_consumption(&member, &47, &(i as u32), &opening));
+        }
+        let opening = ConsumptionOpening::V1(kwh[1], secret.clone());
+        assert!(!client.verify_private_consumption(&member, &47, &0, &opening));
+        assert!(!client.verify_private_consumption(&member, &47, &3, &opening));
+
+        // La apertura v1 está ligada al usuario y al período
+        let opening = ConsumptionOpening::V1(kwh[0], secret.clone());
+        assert!(!client.verify_private_consumption(&member, &48, &0, &opening));
+        client.record_private_consumption(&members.get(1).unwrap(), &47, &commitments[0]);
+        assert!(!client.verify_private_consumption(&members.get(1).unwrap(), &47, &0, &opening));
+
+        // El formato v0 sigue verificable
+        let opening = ConsumptionOpening::V0(address_bytes, 3, secret.clone());
+        assert!(client.verify_private_consumption(&member, &48, &0, &opening));
+
+        // Paginación
+        let page = client.get_commitment_history(&member, &47, &0, &2);
+        assert_eq!(page.len(), 2);
+        assert_eq!(page.get(0).unwrap().timestamp, 48 * PERIOD_SECONDS + 60);
+        assert_eq!(page.get(1).unwrap().commitment, commitments[1]);
+        let page = client.get_commitment_history(&member, &47, &2, &2);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page.get(0).unwrap().timestamp, 48 * PERIOD_SECONDS + 180);
+        assert!(client.get_commitment_history(&member, &47, &5, &2).is_empty());
+
+        let result = client.try_get_commitment_history(&member, &47, &0, &0);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidRange)));
+        let result = client.try_get_commitment_history(&member, &47, &0, &(MAX_PAGE_SIZE + 1));
+        assert_eq!(result, Err(Ok(DistributionError::InvalidRange)));
+
+        let result = client.try_record_private_consumption(&member, &49, &secret);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidPeriod)));
+    }
+
+    #[test]
+    fn test_commit_reveal_settlement_burns_hdrop() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 20 * PERIOD_SECONDS);
+
+        let token_id = env.register(MockToken, ());
+        let token = MockTokenClient::new(&env, &token_id);
         let admin = Address::generate(&env);
-        let token_contract = Address::generate(&env);
-        let investor1 = Address::generate(&env);
-        let investor2 = Address::generate(&env);
-        let investor3 = Address::generate(&env);
-        let investor4 = Address::generate(&env);
-        let investor5 = Address::generate(&env);
+        let household1 = Address::generate(&env);
+        let household2 = Address::generate(&env);
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_id, &1u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+
+        client.add_members_multisig(
+            &vec![&env, household1.clone()],
+            &vec![&env, household1.clone(), household2.clone()],
+            &vec![&env, 6_000, 4_000],
+        );
+        client.record_generation(&admin, &20, &100_0000000);
 
-        client.try_initialize(&admin, &token_contract, &3).unwrap();
+        let secret = BytesN::from_array(&env, &[2u8; 32]);
+        let commitment = v1_commitment(&env, &contract_id, &household1, 20, 25_0000000, &secret);
 
-        let approvers = vec![
-            &env,
-            investor1.clone(),
-            investor2.clone(),
-            investor3.clone(),
-        ];
-        let members = vec![
-            &env,
-            investor1.clone(),
-            investor2.clone(),
-            investor3.clone(),
-            investor4.clone(),
-            investor5.clone(),
-        ];
-        let percents = vec![&env, 20, 30, 15, 25, 10];
+        // household2 copia el commitment de household1
+        client.commit_settlement(&household1, &20, &commitment);
+        client.commit_settlement(&household2, &20, &commitment);
+        let result = client.try_commit_settlement(&household1, &20, &commitment);
+        assert_eq!(result, Err(Ok(DistributionError::CommitmentAlreadyRecorded)));
 
-        let result = client.try_add_members_multisig(&approvers, &members, &percents);
-        assert!(result.is_ok());
+        let deadline = 21 * PERIOD_SECONDS + REVEAL_WINDOW;
+        assert_eq!(client.get_settlement(&household1, &20).unwrap().deadline, deadline);
 
-        assert!(client.is_member(&investor1));
-        assert!(client.is_member(&investor2));
-        assert!(client.is_member(&investor3));
-        assert!(client.is_member(&investor4));
-        assert!(client.is_member(&investor5));
+        // Un reveal que no corresponde al commitment no quema nada
+        let result = client.try_reveal_settlement(&household1, &20, &30_0000000, &secret);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidReveal)));
 
-        assert_eq!(client.get_member_percent(&investor1), Some(20));
-        assert_eq!(client.get_member_percent(&investor2), Some(30));
-        assert_eq!(client.get_member_percent(&investor3), Some(15));
-        assert_eq!(client.get_member_percent(&investor4), Some(25));
-        assert_eq!(client.get_member_percent(&investor5), Some(10));
+        // La copia no se puede abrir: el commitment está ligado a household1
+        let result = client.try_reveal_settlement(&household2, &20, &25_0000000, &secret);
+        assert_eq!(result, Err(Ok(DistributionError::InvalidReveal)));
 
-        assert!(client.are_members_initialized());
+        client.reveal_settlement(&household1, &20, &25_0000000, &secret);
+        assert_eq!(token.balance(&household1), 35_0000000);
+        assert_eq!(
+            client.get_settlement(&household1, &20).unwrap().status,
+            SettlementStatus::Revealed(25_0000000)
+        );
+        let result = client.try_reveal_settlement(&household1, &20, &25_0000000, &secret);
+        assert_eq!(result, Err(Ok(DistributionError::SettlementClosed)));
+
+        // household2 no revela: antes del plazo no se puede penalizar
+        let result = client.try_penalize_settlement(&household2, &20);
+        assert_eq!(result, Err(Ok(DistributionError::RevealWindowOpen)));
+
+        env.ledger().with_mut(|li| li.timestamp = deadline + 1);
+        let result = client.try_reveal_settlement(&household2, &20, &25_0000000, &secret);
+        assert_eq!(result, Err(Ok(DistributionError::RevealDeadlinePassed)));
+
+        // Se quema todo lo asignado en el período
+        assert_eq!(client.penalize_settlement(&household2, &20), 40_0000000);
+        assert_eq!(token.balance(&household2), 0);
+        assert_eq!(
+            client.get_settlement(&household2, &20).unwrap().status,
+            SettlementStatus::Penalized(40_0000000)
+        );
+        let result = client.try_penalize_settlement(&household2, &20);
+        assert_eq!(result, Err(Ok(DistributionError::SettlementClosed)));
 
-        let member_list = client.get_member_list();
-        assert_eq!(member_list.len(), 5);
+        // Ya no se puede comprometer un período vencido
+        let result = client.try_commit_settlement(&household1, &19, &commitment);
+        assert_eq!(result, Err(Ok(DistributionError::RevealDeadlinePassed)));
+    }
+
+    #[test]
+    fn test_reveal_settlement_rejects_invalid_amounts() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 20 * PERIOD_SECONDS);
+
+        let token_id = env.register(MockToken, ());
+        let token = MockTokenClient::new(&env, &token_id);
+        let admin = Address::generate(&env);
+        let household = Address::generate(&env);
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_id, &1u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+
+        client.add_members_multisig(
+            &vec![&env, household.clone()],
+            &vec![&env, household.clone()],
+            &vec![&env, 10_000],
+        );
+        client.record_generation(&admin, &20, &60_0000000);
+
+        // El commitment abre con más de lo que el miembro tiene
+        let secret = BytesN::from_array(&env, &[2u8; 32]);
+        let commitment = v1_commitment(&env, &contract_id, &household, 20, 70_0000000, &secret);
+        client.commit_settlement(&household, &20, &commitment);
+
+        for kwh in [0i128, -10_0000000] {
+            let result = client.try_reveal_settlement(&household, &20, &kwh, &secret);
+            assert_eq!(result, Err(Ok(DistributionError::InvalidAmount)));
+        }
+        let result = client.try_reveal_settlement(&household, &20, &70_0000000, &secret);
+        assert_eq!(result, Err(Ok(DistributionError::InsufficientBalance)));
+
+        // Nada cambió: la liquidación sigue pendiente y el balance intacto
+        assert_eq!(token.balance(&household), 60_0000000);
+        assert_eq!(
+            client.get_settlement(&household, &20).unwrap().status,
+            SettlementStatus::Pending
+        );
+    }
+
+    #[test]
+    fn test_privacy_mode_is_enforced_and_hides_member_events() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 48 * PERIOD_SECONDS);
+
+        let token_id = env.register(MockToken, ());
+        let admin = Address::generate(&env);
+        let governance = Address::generate(&env);
+        let members = vec![&env, Address::generate(&env), Address::generate(&env)];
+        let contract_id = env.register(EnergyDistribution, (&admin, &token_id, &1u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+        let shares_bps = vec![&env, 6_000, 4_000];
+        client.add_members_multisig(&vec![&env, admin.clone()], &members, &shares_bps);
+        client.set_governance(&admin, &governance);
+
+        let member = members.get(0).unwrap();
+        let commitment = BytesN::from_array(&env, &[7u8; 32]);
+
+        // Deshabilitado: los entrypoints privados se rechazan
+        assert!(!client.is_privacy_enabled());
+        let result = client.try_record_private_consumption(&member, &47, &commitment);
+        assert_eq!(result, Err(Ok(DistributionError::PrivacyDisabled)));
+        let blinding = Fr::from_u256(soroban_sdk::U256::from_u32(&env, 1));
+        let pedersen = privacy::pedersen_commit(&env, 1, &blinding);
+        let result = client.try_commit_consumption(&member, &47, &pedersen);
+        assert_eq!(result, Err(Ok(DistributionError::PrivacyDisabled)));
+
+        // Solo PRIVACY_ROLE o la gobernanza cambian el modo
+        let result = client.try_enable_privacy(&member);
+        assert_eq!(result, Err(Ok(DistributionError::NotAuthorized)));
+        client.enable_privacy(&governance);
+        assert!(client.is_privacy_enabled());
+
+        let outsider = Address::generate(&env);
+        let result = client.try_record_private_consumption(&outsider, &47, &commitment);
+        assert_eq!(result, Err(Ok(DistributionError::NotAMember)));
+        client.record_private_consumption(&member, &47, &commitment);
+
+        // La generación solo publica el total
+        client.record_generation(&admin, &47, &10);
+        assert_eq!(
+            env.events().all(),
+            std::vec![events::GenerationTotalRecorded {
+                period: 47,
+                kwh: 10,
+            }
+            .to_xdr(&env, &contract_id)]
+        );
+
+        // La liquidación no publica la cantidad quemada
+        let secret = BytesN::from_array(&env, &[3u8; 32]);
+        let settlement = v1_commitment(&env, &contract_id, &member, 47, 5, &secret);
+        client.commit_settlement(&member, &47, &settlement);
+        client.reveal_settlement(&member, &47, &5, &secret);
+        assert_eq!(
+            env.events().all(),
+            std::vec![events::SettlementClosed {
+                user: member.clone(),
+                period: 47,
+                penalized: false,
+            }
+            .to_xdr(&env, &contract_id)]
+        );
+
+        // Al deshabilitarlo vuelve el reparto por miembro y los commitments
+        // guardados siguen verificables
+        client.disable_privacy(&admin);
+        assert!(!client.is_privacy_enabled());
+        let result = client.try_record_private_consumption(&member, &47, &commitment);
+        assert_eq!(result, Err(Ok(DistributionError::PrivacyDisabled)));
+        assert_eq!(client.get_commitment_count(&member, &47), 1);
+
+        client.record_generation(&admin, &46, &10);
+        assert_eq!(
+            env.events().all(),
+            std::vec![events::GenerationRecorded {
+                period: 46,
+                kwh: 10,
+                members,
+                amounts: vec![&env, 6, 4],
+            }
+            .to_xdr(&env, &contract_id)]
+        );
+    }
+
+    #[test]
+    fn test_pause_blocks_generation_and_membership_changes() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 10 * PERIOD_SECONDS);
+
+        let (client, admin, members) = setup_community(&env);
+        let member = members.get(0).unwrap();
+        let governance = Address::generate(&env);
+        client.set_governance(&admin, &governance);
+
+        // Solo PAUSER_ROLE pausa
+        let result = client.try_pause(&member);
+        assert_eq!(result, Err(Ok(DistributionError::NotAuthorized)));
+
+        client.pause(&admin);
+        assert_eq!(
+            env.events().all(),
+            std::vec![events::Paused {
+                caller: admin.clone()
+            }
+            .to_xdr(&env, &client.address)]
+        );
+        assert!(client.is_paused());
+
+        let result = client.try_record_generation(&admin, &1, &10);
+        assert_eq!(result, Err(Ok(DistributionError::ContractPaused)));
+        let approvers = vec![&env, member.clone(), members.get(1).unwrap()];
+        let shares_bps = vec![&env, 5_000, 3_000, 2_000];
+        let result = client.try_add_members_multisig(&approvers, &members, &shares_bps);
+        assert_eq!(result, Err(Ok(DistributionError::ContractPaused)));
+        let result = client.try_update_members(&members, &shares_bps);
+        assert_eq!(result, Err(Ok(DistributionError::ContractPaused)));
+        let action = MultisigAction::SetRequiredApprovals(1);
+        let result = client.try_propose_action(&member, &action, &3_600);
+        assert_eq!(result, Err(Ok(DistributionError::ContractPaused)));
+
+        // Las consultas siguen funcionando
+        assert_eq!(client.get_member_share(&member), Some(5_000));
+        assert_eq!(client.get_total_generated(), 0);
+
+        let result = client.try_unpause(&member);
+        assert_eq!(result, Err(Ok(DistributionError::NotAuthorized)));
+        client.unpause(&admin);
+        assert!(!client.is_paused());
+
+        client.propose_action(&member, &action, &3_600);
+        client.update_members(&members, &shares_bps);
+        assert_eq!(client.get_member_share(&member), Some(5_000));
     }
 }
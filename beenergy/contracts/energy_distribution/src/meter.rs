@@ -0,0 +1,49 @@
+//! # Meter Module - Lecturas firmadas por medidores
+//!
+//! Permite que medidores IoT registrados envíen lecturas de generación
+//! firmadas con su clave ed25519, sin pasar por el admin.
+//!
+//! ## Formato:
+//! - Cada medidor se identifica por su clave pública ed25519 (32 bytes)
+//! - La lectura incluye el intervalo medido y los kWh acumulados del medidor
+//! - El mensaje firmado es el XDR de `(contract_address, reading)`, así una
+//!   lectura no puede reutilizarse en otro contrato de distribución
+//! - El contrato mintea solo la diferencia con la última lectura aceptada
+
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env};
+
+/// Lectura de generación firmada por un medidor
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MeterReading {
+    pub meter: BytesN<32>,       // Clave pública ed25519 del medidor
+    pub interval_start: u64,     // Inicio del intervalo medido (timestamp)
+    pub interval_end: u64,       // Fin del intervalo medido (timestamp)
+    pub cumulative_kwh: i128,    // kWh acumulados por el medidor (7 decimales)
+}
+
+/// Estado de un medidor registrado
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MeterState {
+    pub active: bool,
+    pub last_interval_end: u64,     // Fin del último intervalo aceptado
+    pub last_cumulative_kwh: i128,  // Lectura acumulada ya minteada
+}
+
+/// Mensaje que el medidor debe firmar para una lectura
+///
+/// # Retorna
+/// El XDR de la tupla `(contract, reading)`
+pub fn reading_message(env: &Env, contract: &Address, reading: &MeterReading) -> Bytes {
+    (contract.clone(), reading.clone()).to_xdr(env)
+}
+
+/// Verifica la firma ed25519 de una lectura
+///
+/// Aborta la invocación si la firma no es válida (host error de crypto).
+pub fn verify_reading(env: &Env, reading: &MeterReading, signature: &BytesN<64>) {
+    let message = reading_message(env, &env.current_contract_address(), reading);
+    env.crypto()
+        .ed25519_verify(&reading.meter, &message, signature);
+}
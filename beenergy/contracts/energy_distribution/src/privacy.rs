@@ -1,28 +1,122 @@
-//! # Privacy Module - ZK Proof Simulation
+//! # Privacy Module - Commitments y proofs Groth16
 //!
-//! Este módulo implementa privacidad básica usando commitments (hashes).
-//! Para producción, esto debería reemplazarse con ZK-SNARKs reales (Groth16).
+//! Los miembros registran su consumo como un commitment (hash) y después
+//! demuestran que no consumieron más de lo asignado con un proof Groth16
+//! sobre BLS12-381, verificado con las host functions de Soroban.
 //!
 //! ## Concepto:
 //! - Los usuarios pueden registrar consumo de forma privada
 //! - Se usa un "commitment" (hash) en lugar de revelar la cantidad exacta
-//! - El contrato verifica que el commitment es válido sin conocer el valor real
+//! - El proof demuestra `consumido <= asignado` en un período sin revelar el consumo
 //!
-//! ## Para Hackathon:
-//! Esto demuestra el concepto de privacidad sin implementar circuitos ZK completos.
+//! ## Formato de commitment:
+//! - v1 (actual, `encode_consumption_v1`): tag de dominio + versión + network
+//!   id + contrato + `Address` del usuario + período + kWh + secret. Un
+//!   commitment no sirve en otra red, otro contrato, otro usuario ni otro período
+//! - v0 (legacy, `hash_consumption_data`): 32 bytes de dirección elegidos por el
+//!   usuario + kWh + secret, sin separación de dominio. Los commitments v0 ya
+//!   guardados se siguen pudiendo abrir con `ConsumptionOpening::V0`
 //!
-//! ## Para Producción:
-//! Reemplazar con Circom + SnarkJS para ZK proofs reales.
+//! ## Circuito (off-chain, Circom + SnarkJS):
+//! - Privados: `user_data` (formato v1), `consumed_kwh`
+//! - Públicos, en este orden (ver `consumption_public_inputs`):
+//!   1. `allocated_kwh`: lo asignado al miembro en el período del commitment
+//!   2. `commitment_hi`: primeros 16 bytes del commitment guardado
+//!   3. `commitment_lo`: últimos 16 bytes del commitment guardado
+//! - Restricciones: `SHA256(user_data) == commitment` y `consumed_kwh <= allocated_kwh`
+//!
+//! El commitment se parte en dos mitades de 128 bits para que cada una entre
+//! en el campo escalar `Fr` sin reducción modular.
+//!
+//! ## Commitments Pedersen (agregados privados):
+//! - `C = kwh·G + r·H` en G1 de BLS12-381, con `r` un factor de cegado secreto
+//! - `G` y `H` salen de `hash_to_g1`, así nadie conoce el logaritmo de `H` en base `G`
+//! - Son aditivos: `C1 + C2 = (kwh1 + kwh2)·G + (r1 + r2)·H`, por lo que la
+//!   suma de los commitments de un período compromete el consumo total
+//! - Para abrir solo el total, el auditor necesita la suma de los factores de
+//!   cegado (coordinada off-chain; si los miembros eligen factores que suman
+//!   cero, el total se abre con `r = 0`)
+
+use soroban_sdk::{
+    contracttype,
+    crypto::bls12_381::{Fr, G1Affine, G2Affine},
+    vec,
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, Vec, U256,
+};
+
+/// Tag de separación de dominio de los commitments de consumo
+pub const COMMITMENT_DOMAIN: &[u8] = b"BEENERGY-CONSUMPTION";
+
+/// Versión actual del formato de commitment
+pub const COMMITMENT_VERSION: u8 = 1;
+
+/// Datos para abrir un commitment de consumo SHA256
+///
+/// El usuario, el período y el contrato los aporta quien verifica; la
+/// apertura solo contiene lo que el usuario mantuvo en secreto.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConsumptionOpening {
+    /// Formato v0 (legacy): (dirección de 32 bytes, kWh, secret)
+    V0(BytesN<32>, i128, BytesN<32>),
+    /// Formato v1: (kWh, secret)
+    V1(i128, BytesN<32>),
+}
+
+/// Cantidad de entradas públicas del circuito de consumo
+pub const CONSUMPTION_PUBLIC_INPUTS: u32 = 3;
+
+/// Verification key Groth16 del circuito de consumo
+///
+/// `ic` tiene una entrada por cada input público más la constante inicial
+/// (`CONSUMPTION_PUBLIC_INPUTS + 1` puntos).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerificationKey {
+    pub alpha: G1Affine,
+    pub beta: G2Affine,
+    pub gamma: G2Affine,
+    pub delta: G2Affine,
+    pub ic: Vec<G1Affine>,
+}
+
+/// Proof Groth16 (puntos sin comprimir, formato de las host functions)
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Groth16Proof {
+    pub a: G1Affine,
+    pub b: G2Affine,
+    pub c: G1Affine,
+}
 
-use soroban_sdk::{Bytes, BytesN, Env};
+/// Domain separation tag para derivar los generadores Pedersen
+const PEDERSEN_DST: &[u8] = b"BEENERGY-PEDERSEN-V01-CS01-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
 
-/// Commitment de consumo privado
+/// Commitment Pedersen de consumo privado, tal como se guarda por período
 ///
-/// En un sistema ZK real, esto sería un proof Groth16.
-/// Para la demo, usamos: commitment = SHA256(user_address + consumed_kwh + secret)
+/// commitment = consumed_kwh·G + blinding·H
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ConsumptionCommitment {
-    pub commitment: BytesN<32>,  // Hash del consumo
-    pub timestamp: u64,          // Cuándo se registró
+    pub commitment: G1Affine,   // Punto que oculta el consumo
+    pub timestamp: u64,         // Cuándo se registró
+}
+
+/// Commitment SHA256 de consumo en el historial de un usuario
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentRecord {
+    pub commitment: BytesN<32>, // SHA256(user_data)
+    pub timestamp: u64,         // Cuándo se registró
+}
+
+/// Suma de los commitments Pedersen de un período
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AggregateCommitment {
+    pub commitment: G1Affine,   // Σ commitments de los miembros
+    pub count: u32,             // Cantidad de commitments sumados
 }
 
 /// Genera un commitment de consumo
@@ -33,8 +127,6 @@ pub struct ConsumptionCommitment {
 /// # Retorna
 /// Un commitment (hash SHA256) que oculta la cantidad consumida
 pub fn generate_commitment(env: &Env, user_data: &Bytes) -> BytesN<32> {
-    // En producción: esto sería un ZK proof (Groth16)
-    // Para hackathon: usamos SHA256 como commitment
     env.crypto().sha256(user_data).into()
 }
 
@@ -47,14 +139,17 @@ pub fn generate_commitment(env: &Env, user_data: &Bytes) -> BytesN<32> {
 /// # Retorna
 /// `true` si el commitment es válido, `false` si no
 ///
-/// NOTA: En un sistema ZK real, NO recibiríamos user_data (eso revelaría la info).
-/// En su lugar, verificaríamos el proof matemáticamente sin conocer los datos.
+/// NOTA: Requiere revelar `user_data`. Para demostrar cumplimiento sin
+/// revelar el consumo se usa `verify_groth16`.
 pub fn verify_commitment(env: &Env, commitment: &BytesN<32>, user_data: &Bytes) -> bool {
     let computed = generate_commitment(env, user_data);
     commitment == &computed
 }
 
-/// Calcula el hash de datos de usuario para commitment
+/// Calcula el hash de datos de usuario para commitment (formato v0, legacy)
+///
+/// Solo se conserva para abrir commitments v0 ya guardados: los nuevos
+/// commitments usan `encode_consumption_v1`.
 ///
 /// # Formato de user_data:
 /// ```
@@ -86,10 +181,289 @@ pub fn hash_consumption_data(
     data
 }
 
+/// Codifica los datos de un commitment de consumo en formato v1
+///
+/// # Formato:
+/// ```text
+/// "BEENERGY-CONSUMPTION" (20 bytes) +
+/// versión (1 byte, 0x01) +
+/// network_id (32 bytes) +
+/// XDR del contrato (ScVal Address) +
+/// XDR del usuario (ScVal Address) +
+/// period (8 bytes, u64 big-endian) +
+/// consumed_kwh (16 bytes, i128 big-endian) +
+/// secret (32 bytes)
+/// ```
+pub fn encode_consumption_v1(
+    env: &Env,
+    network_id: &BytesN<32>,
+    contract: &Address,
+    user: &Address,
+    period: u64,
+    consumed_kwh: i128,
+    secret: &BytesN<32>,
+) -> Bytes {
+    let mut data = Bytes::from_slice(env, COMMITMENT_DOMAIN);
+    data.push_back(COMMITMENT_VERSION);
+    data.append(&network_id.clone().into());
+    data.append(&contract.clone().to_xdr(env));
+    data.append(&user.clone().to_xdr(env));
+    data.extend_from_array(&period.to_be_bytes());
+    data.extend_from_array(&consumed_kwh.to_be_bytes());
+    data.append(&secret.clone().into());
+    data
+}
+
+/// Codifica una apertura para `user` y `period` en este contrato y red
+pub fn encode_opening(
+    env: &Env,
+    user: &Address,
+    period: u64,
+    opening: &ConsumptionOpening,
+) -> Bytes {
+    match opening {
+        ConsumptionOpening::V0(user_address, consumed_kwh, secret) => {
+            hash_consumption_data(env, user_address, *consumed_kwh, secret)
+        }
+        ConsumptionOpening::V1(consumed_kwh, secret) => encode_consumption_v1(
+            env,
+            &env.ledger().network_id(),
+            &env.current_contract_address(),
+            user,
+            period,
+            *consumed_kwh,
+            secret,
+        ),
+    }
+}
+
+/// kWh revelados por una apertura
+pub fn opening_kwh(opening: &ConsumptionOpening) -> i128 {
+    match opening {
+        ConsumptionOpening::V0(_, consumed_kwh, _) | ConsumptionOpening::V1(consumed_kwh, _) => {
+            *consumed_kwh
+        }
+    }
+}
+
+/// Verifica la apertura de un commitment de `user` en `period`
+///
+/// # Retorna
+/// `false` si los kWh son negativos o la apertura no corresponde
+pub fn verify_opening(
+    env: &Env,
+    commitment: &BytesN<32>,
+    user: &Address,
+    period: u64,
+    opening: &ConsumptionOpening,
+) -> bool {
+    opening_kwh(opening) >= 0
+        && verify_commitment(env, commitment, &encode_opening(env, user, period, opening))
+}
+
+/// Construye los inputs públicos del circuito de consumo
+///
+/// # Argumentos
+/// * `allocated_kwh` - Asignado al miembro en el período (no negativo)
+/// * `commitment` - Commitment de consumo guardado por el miembro
+pub fn consumption_public_inputs(
+    env: &Env,
+    allocated_kwh: i128,
+    commitment: &BytesN<32>,
+) -> Vec<Fr> {
+    let bytes = commitment.to_array();
+    let mut hi = [0u8; 16];
+    let mut lo = [0u8; 16];
+    hi.copy_from_slice(&bytes[..16]);
+    lo.copy_from_slice(&bytes[16..]);
+
+    vec![
+        env,
+        Fr::from_u256(U256::from_u128(env, allocated_kwh as u128)),
+        Fr::from_u256(U256::from_u128(env, u128::from_be_bytes(hi))),
+        Fr::from_u256(U256::from_u128(env, u128::from_be_bytes(lo))),
+    ]
+}
+
+/// Verifica un proof Groth16 sobre BLS12-381
+///
+/// Comprueba `e(A, B) = e(alpha, beta) · e(vk_x, gamma) · e(C, delta)` con
+/// `vk_x = ic[0] + Σ inputs[i] · ic[i + 1]`, como un único pairing check.
+///
+/// # Retorna
+/// `false` si el proof no es válido o si la cantidad de inputs no coincide
+/// con la verification key
+pub fn verify_groth16(
+    env: &Env,
+    vk: &VerificationKey,
+    proof: &Groth16Proof,
+    public_inputs: &Vec<Fr>,
+) -> bool {
+    if vk.ic.len() != public_inputs.len() + 1 {
+        return false;
+    }
+
+    let bls = env.crypto().bls12_381();
+
+    let mut vk_x = vk.ic.get(0).unwrap();
+    for (input, point) in public_inputs.iter().zip(vk.ic.iter().skip(1)) {
+        vk_x = bls.g1_add(&vk_x, &bls.g1_mul(&point, &input));
+    }
+
+    let g1_points = vec![
+        env,
+        -proof.a.clone(),
+        vk.alpha.clone(),
+        vk_x,
+        proof.c.clone(),
+    ];
+    let g2_points = vec![
+        env,
+        proof.b.clone(),
+        vk.beta.clone(),
+        vk.gamma.clone(),
+        vk.delta.clone(),
+    ];
+    bls.pairing_check(g1_points, g2_points)
+}
+
+/// Generadores `(G, H)` de los commitments Pedersen
+pub fn pedersen_generators(env: &Env) -> (G1Affine, G1Affine) {
+    let bls = env.crypto().bls12_381();
+    let dst = Bytes::from_slice(env, PEDERSEN_DST);
+    let g = bls.hash_to_g1(&Bytes::from_slice(env, b"G"), &dst);
+    let h = bls.hash_to_g1(&Bytes::from_slice(env, b"H"), &dst);
+    (g, h)
+}
+
+/// Calcula el commitment Pedersen `kwh·G + blinding·H`
+///
+/// # Argumentos
+/// * `kwh` - Cantidad comprometida (no negativa)
+/// * `blinding` - Factor de cegado secreto
+pub fn pedersen_commit(env: &Env, kwh: i128, blinding: &Fr) -> G1Affine {
+    let bls = env.crypto().bls12_381();
+    let (g, h) = pedersen_generators(env);
+    let value = Fr::from_u256(U256::from_u128(env, kwh as u128));
+    bls.g1_add(&bls.g1_mul(&g, &value), &bls.g1_mul(&h, blinding))
+}
+
+/// Suma dos commitments Pedersen
+pub fn add_commitments(env: &Env, lhs: &G1Affine, rhs: &G1Affine) -> G1Affine {
+    env.crypto().bls12_381().g1_add(lhs, rhs)
+}
+
+/// Verifica la apertura `(kwh, blinding)` de un commitment Pedersen
+///
+/// # Retorna
+/// `false` si `kwh` es negativo o no corresponde al commitment
+pub fn open_commitment(env: &Env, commitment: &G1Affine, kwh: i128, blinding: &Fr) -> bool {
+    if kwh < 0 {
+        return false;
+    }
+    &pedersen_commit(env, kwh, blinding) == commitment
+}
+
+/// Indica si el punto es un commitment Pedersen válido (en el subgrupo de G1)
+pub fn is_valid_commitment(env: &Env, commitment: &G1Affine) -> bool {
+    env.crypto().bls12_381().g1_is_in_subgroup(commitment)
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
-    use soroban_sdk::{Bytes, BytesN, Env};
+    use soroban_sdk::{testutils::Address as _, Bytes, BytesN, Env};
+
+    /// Genera una verification key y un proof válido para `public_inputs`
+    /// usando el trapdoor del setup (escalares conocidos).
+    ///
+    /// Sirve para probar el verificador sin un circuito compilado: con
+    /// `alpha = a·P`, `beta = b·Q`, `gamma = g·Q`, `delta = d·Q`, `C = c·P`,
+    /// `B = Q` y `A = (a·b + x·g + c·d)·P` la ecuación de Groth16 se cumple.
+    pub(crate) fn simulated_proof(
+        env: &Env,
+        public_inputs: &Vec<Fr>,
+    ) -> (VerificationKey, Groth16Proof) {
+        let bls = env.crypto().bls12_381();
+        let dst = Bytes::from_slice(env, b"BEENERGY-GROTH16-TEST");
+        let p = bls.hash_to_g1(&Bytes::from_slice(env, b"P"), &dst);
+        let q = bls.hash_to_g2(&Bytes::from_slice(env, b"Q"), &dst);
+        let fr = |n: u64| Fr::from_u256(U256::from_u32(env, n as u32));
+
+        let (a, b, g, d, c) = (fr(2), fr(3), fr(5), fr(7), fr(11));
+
+        let mut ic = Vec::new(env);
+        let mut x = fr(13);
+        ic.push_back(bls.g1_mul(&p, &x));
+        for (i, input) in public_inputs.iter().enumerate() {
+            let u = fr(17 + i as u64);
+            ic.push_back(bls.g1_mul(&p, &u));
+            x = bls.fr_add(&x, &bls.fr_mul(&input, &u));
+        }
+
+        let a_scalar = bls.fr_add(
+            &bls.fr_add(&bls.fr_mul(&a, &b), &bls.fr_mul(&x, &g)),
+            &bls.fr_mul(&c, &d),
+        );
+
+        let vk = VerificationKey {
+            alpha: bls.g1_mul(&p, &a),
+            beta: bls.g2_mul(&q, &b),
+            gamma: bls.g2_mul(&q, &g),
+            delta: bls.g2_mul(&q, &d),
+            ic,
+        };
+        let proof = Groth16Proof {
+            a: bls.g1_mul(&p, &a_scalar),
+            b: q,
+            c: bls.g1_mul(&p, &c),
+        };
+        (vk, proof)
+    }
+
+    #[test]
+    fn test_verify_groth16() {
+        let env = Env::default();
+        // Varios pairings fuera de una invocación superan el presupuesto por defecto
+        env.cost_estimate().budget().reset_unlimited();
+
+        let commitment = BytesN::from_array(&env, &[3u8; 32]);
+        let inputs = consumption_public_inputs(&env, 500_0000000, &commitment);
+        let (vk, proof) = simulated_proof(&env, &inputs);
+
+        assert!(verify_groth16(&env, &vk, &proof, &inputs));
+
+        // Otro total asignado no verifica con el mismo proof
+        let other_inputs = consumption_public_inputs(&env, 600_0000000, &commitment);
+        assert!(!verify_groth16(&env, &vk, &proof, &other_inputs));
+
+        // Proof alterado
+        let bls = env.crypto().bls12_381();
+        let mut tampered = proof.clone();
+        tampered.c = bls.g1_add(&proof.c, &proof.c);
+        assert!(!verify_groth16(&env, &vk, &tampered, &inputs));
+
+        // Cantidad de inputs distinta a la verification key
+        let mut short_inputs = inputs.clone();
+        short_inputs.pop_back();
+        assert!(!verify_groth16(&env, &vk, &proof, &short_inputs));
+    }
+
+    #[test]
+    fn test_consumption_public_inputs_split_commitment() {
+        let env = Env::default();
+
+        let mut bytes = [0u8; 32];
+        bytes[15] = 1; // último byte de la mitad alta
+        bytes[31] = 2; // último byte de la mitad baja
+        let commitment = BytesN::from_array(&env, &bytes);
+
+        let inputs = consumption_public_inputs(&env, 42, &commitment);
+        assert_eq!(inputs.len(), CONSUMPTION_PUBLIC_INPUTS);
+        assert_eq!(inputs.get(0).unwrap().to_u256(), U256::from_u32(&env, 42));
+        assert_eq!(inputs.get(1).unwrap().to_u256(), U256::from_u32(&env, 1));
+        assert_eq!(inputs.get(2).unwrap().to_u256(), U256::from_u32(&env, 2));
+    }
 
     #[test]
     fn test_generate_commitment() {
@@ -166,4 +540,53 @@ mod tests {
         // Datos diferentes = commitments diferentes
         assert_ne!(commitment1, commitment2);
     }
+
+    #[test]
+    fn test_pedersen_commitments_are_additive() {
+        let env = Env::default();
+        env.cost_estimate().budget().reset_unlimited();
+        let bls = env.crypto().bls12_381();
+        let fr = |n: u32| Fr::from_u256(U256::from_u32(&env, n));
+
+        let c1 = pedersen_commit(&env, 30_0000000, &fr(1_111));
+        let c2 = pedersen_commit(&env, 12_0000000, &fr(2_222));
+        assert!(is_valid_commitment(&env, &c1));
+
+        // La suma se abre con el total y la suma de los factores de cegado
+        let total = add_commitments(&env, &c1, &c2);
+        let blinding = bls.fr_add(&fr(1_111), &fr(2_222));
+        assert!(open_commitment(&env, &total, 42_0000000, &blinding));
+        assert!(!open_commitment(&env, &total, 41_0000000, &fr(3_333)));
+
+        // El mismo consumo con otro factor de cegado da otro commitment
+        assert_ne!(c1, pedersen_commit(&env, 30_0000000, &fr(9_999)));
+        assert!(!open_commitment(&env, &c1, -1, &fr(1_111)));
+    }
+
+    #[test]
+    fn test_v1_commitment_binds_context() {
+        let env = Env::default();
+
+        let network = BytesN::from_array(&env, &[1u8; 32]);
+        let contract = Address::generate(&env);
+        let user = Address::generate(&env);
+        let secret = BytesN::from_array(&env, &[2u8; 32]);
+        let commit = |network: &BytesN<32>, contract: &Address, user: &Address, period: u64| {
+            let data = encode_consumption_v1(&env, network, contract, user, period, 50, &secret);
+            generate_commitment(&env, &data)
+        };
+
+        let base = commit(&network, &contract, &user, 7);
+        let data = encode_consumption_v1(&env, &network, &contract, &user, 7, 50, &secret);
+        assert_eq!(data.slice(..20), Bytes::from_slice(&env, COMMITMENT_DOMAIN));
+        assert_eq!(data.get(20), Some(COMMITMENT_VERSION));
+
+        // Cambiar cualquier parte del contexto cambia el commitment
+        let other_network = BytesN::from_array(&env, &[3u8; 32]);
+        let other = Address::generate(&env);
+        assert_ne!(base, commit(&other_network, &contract, &user, 7));
+        assert_ne!(base, commit(&network, &other, &user, 7));
+        assert_ne!(base, commit(&network, &contract, &other, 7));
+        assert_ne!(base, commit(&network, &contract, &user, 8));
+    }
 }
@@ -0,0 +1,141 @@
+//! # Split Module - Reparto sin polvo
+//!
+//! Calcula cuántos tokens le corresponden a cada miembro a partir de los kWh
+//! generados y las participaciones en puntos básicos (10_000 = 100%).
+//!
+//! ## Política de remanente:
+//! - Cada miembro recibe `floor(kwh * share_bps / 10_000)`, calculado sin
+//!   desbordar aunque `kwh` esté cerca de `i128::MAX`
+//! - Las unidades que sobran por el redondeo (como máximo `n - 1`) van al
+//!   miembro con mayor participación; ante empate, al primero de la lista
+//! - Así la suma minteada es siempre igual a los kWh registrados
+
+use soroban_sdk::{Env, Vec};
+
+use crate::TOTAL_SHARES_BPS;
+
+/// Reparte `kwh_generated` entre los miembros según `shares_bps`
+///
+/// # Argumentos
+/// * `kwh_generated` - Cantidad a repartir (no negativa)
+/// * `shares_bps` - Participaciones en puntos básicos, en el orden de `MemberList`
+///
+/// # Retorna
+/// La cantidad de tokens para cada miembro, en el mismo orden que `shares_bps`.
+/// La suma es exactamente `kwh_generated`.
+pub fn split_generation(env: &Env, kwh_generated: i128, shares_bps: &Vec<u32>) -> Vec<i128> {
+    let mut shares: Vec<i128> = Vec::new(env);
+    let mut distributed: i128 = 0;
+    let mut largest_index: u32 = 0;
+    let mut largest_share_bps: u32 = 0;
+
+    let total_bps = TOTAL_SHARES_BPS as i128;
+    let (whole, rest) = (kwh_generated / total_bps, kwh_generated % total_bps);
+
+    for (i, share_bps) in shares_bps.iter().enumerate() {
+        // floor(kwh * bps / 10_000) = (kwh / 10_000) * bps + (kwh % 10_000) * bps / 10_000
+        let share = whole * share_bps as i128 + rest * share_bps as i128 / total_bps;
+        shares.push_back(share);
+        distributed += share;
+
+        if share_bps > largest_share_bps {
+            largest_share_bps = share_bps;
+            largest_index = i as u32;
+        }
+    }
+
+    // El remanente del redondeo va al mayor propietario
+    let remainder = kwh_generated - distributed;
+    if remainder > 0 && !shares.is_empty() {
+        let share = shares.get(largest_index).unwrap();
+        shares.set(largest_index, share + remainder);
+    }
+
+    shares
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use proptest::prelude::*;
+    use soroban_sdk::{vec, Env};
+
+    #[test]
+    fn test_exact_split() {
+        let env = Env::default();
+
+        let shares_bps = vec![&env, 5_000, 3_000, 2_000];
+        let shares = split_generation(&env, 100_0000000, &shares_bps);
+
+        assert_eq!(shares, vec![&env, 50_0000000, 30_0000000, 20_0000000]);
+    }
+
+    #[test]
+    fn test_remainder_goes_to_largest_holder() {
+        let env = Env::default();
+
+        // 10 unidades entre 33%/34%/33%: 3 + 3 + 3 = 9, sobra 1
+        let shares_bps = vec![&env, 3_300, 3_400, 3_300];
+        let shares = split_generation(&env, 10, &shares_bps);
+
+        assert_eq!(shares, vec![&env, 3, 4, 3]);
+    }
+
+    #[test]
+    fn test_remainder_tie_goes_to_first_member() {
+        let env = Env::default();
+
+        let shares_bps = vec![&env, 2_500, 2_500, 2_500, 2_500];
+        let shares = split_generation(&env, 7, &shares_bps);
+
+        assert_eq!(shares, vec![&env, 4, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_fractional_shares() {
+        let env = Env::default();
+
+        // 12,5% / 33,33% / 54,17%
+        let shares_bps = vec![&env, 1_250, 3_333, 5_417];
+        let shares = split_generation(&env, 100_0000000, &shares_bps);
+
+        assert_eq!(shares, vec![&env, 12_5000000, 33_3300000, 54_1700000]);
+    }
+
+    /// Participaciones aleatorias de 1 a 50 miembros que suman 10_000
+    fn shares_strategy() -> impl Strategy<Value = std::vec::Vec<u32>> {
+        prop::collection::vec(0u32..=TOTAL_SHARES_BPS, 0..50).prop_map(|mut cuts| {
+            cuts.push(0);
+            cuts.push(TOTAL_SHARES_BPS);
+            cuts.sort_unstable();
+            cuts.windows(2).map(|w| w[1] - w[0]).collect()
+        })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn prop_sum_minted_equals_kwh_recorded(
+            kwh in prop_oneof![
+                0i128..=1_000_000_000 * 10_000_000, // hasta 1.000 millones de kWh
+                i128::MAX - 1_000_000..=i128::MAX,  // cerca del límite de i128
+            ],
+            shares_bps in shares_strategy(),
+        ) {
+            let env = Env::default();
+            let shares_bps = Vec::from_slice(&env, &shares_bps);
+
+            let shares = split_generation(&env, kwh, &shares_bps);
+
+            let total: i128 = shares.iter().sum();
+            prop_assert_eq!(total, kwh);
+            prop_assert_eq!(shares.len(), shares_bps.len());
+            for share in shares.iter() {
+                prop_assert!(share >= 0);
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! # Storage Module - Almacenamiento persistente con TTL
+//!
+//! Los datos por miembro (membresía, participación, commitments, asignaciones)
+//! viven en almacenamiento persistente: a diferencia de `instance()`, no se
+//! cargan en cada invocación ni comparten su límite de tamaño.
+//!
+//! ## TTL:
+//! - Cada lectura o escritura de una entrada persistente extiende su TTL
+//!   cuando le quedan menos de `PERSISTENT_TTL_THRESHOLD` ledgers
+//! - La instancia del contrato se extiende en cada cambio de estado
+//! - `bump_storage` extiende todas las entradas de los miembros, y sus
+//!   registros de un rango de períodos, para miembros que no interactúan con
+//!   el contrato por meses
+
+use soroban_sdk::{Env, IntoVal, TryFromVal, Val};
+
+use crate::DataKey;
+
+/// Ledgers por día (un ledger cada ~5 segundos)
+pub const DAY_IN_LEDGERS: u32 = 17_280;
+
+/// Se extiende la instancia cuando le quedan menos de 7 días
+pub const INSTANCE_TTL_THRESHOLD: u32 = 7 * DAY_IN_LEDGERS;
+/// La instancia se extiende hasta 30 días
+pub const INSTANCE_TTL_EXTEND_TO: u32 = 30 * DAY_IN_LEDGERS;
+
+/// Se extiende una entrada persistente cuando le quedan menos de 30 días
+pub const PERSISTENT_TTL_THRESHOLD: u32 = 30 * DAY_IN_LEDGERS;
+/// Las entradas persistentes se extienden hasta 120 días
+pub const PERSISTENT_TTL_EXTEND_TO: u32 = 120 * DAY_IN_LEDGERS;
+
+/// Extiende el TTL de la instancia del contrato
+pub fn extend_instance(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(INSTANCE_TTL_THRESHOLD, INSTANCE_TTL_EXTEND_TO);
+}
+
+/// Extiende el TTL de una entrada persistente si existe
+///
+/// # Retorna
+/// `true` si la entrada existe
+pub fn extend(env: &Env, key: &DataKey) -> bool {
+    if !env.storage().persistent().has(key) {
+        return false;
+    }
+
+    env.storage()
+        .persistent()
+        .extend_ttl(key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+    true
+}
+
+/// Lee una entrada persistente y extiende su TTL
+pub fn get<V: TryFromVal<Env, Val>>(env: &Env, key: &DataKey) -> Option<V> {
+    let value = env.storage().persistent().get(key);
+    if value.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+    }
+    value
+}
+
+/// Guarda una entrada persistente con el TTL extendido
+pub fn set<V: IntoVal<Env, Val>>(env: &Env, key: &DataKey, value: &V) {
+    env.storage().persistent().set(key, value);
+    env.storage()
+        .persistent()
+        .extend_ttl(key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+}
+
+/// Indica si existe una entrada persistente (sin extender su TTL)
+pub fn has(env: &Env, key: &DataKey) -> bool {
+    env.storage().persistent().has(key)
+}
+
+/// Elimina una entrada persistente
+pub fn remove(env: &Env, key: &DataKey) {
+    env.storage().persistent().remove(key);
+}
@@ -0,0 +1,81 @@
+//! # Events Module - Eventos propios del token
+//!
+//! Los eventos SEP-41 (mint, burn, transfer, approve) los publica
+//! OpenZeppelin; aquí están los de la configuración propia de HDROP, los
+//! de la pausa de emergencia, los de vencimiento de vintages y los de
+//! actualización del contrato.
+//!
+//! ## Formato:
+//! - Topics: `[nombre, "v1", ...campos #[topic]]`
+//! - Data: mapa con el resto de los campos (nombre -> valor)
+
+use soroban_sdk::{contractevent, Address, BytesN};
+
+use crate::quota::{MinterQuota, QuotaLimit};
+
+/// El admin configuró la cuota de un minter
+#[contractevent(topics = ["minter_quota_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MinterQuotaSet {
+    #[topic]
+    pub minter: Address,
+    pub quota: MinterQuota,
+}
+
+/// Un minteo agotó un límite de la cuota del minter
+///
+/// Con `Window` el minter puede volver a mintear cuando empiece la próxima
+/// ventana; con `Lifetime`, solo si el admin amplía la cuota.
+#[contractevent(topics = ["minter_quota_exhausted", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MinterQuotaExhausted {
+    #[topic]
+    pub minter: Address,
+    pub limit: QuotaLimit,
+    pub window_start: u32,
+}
+
+/// Se pausaron minteo, quema, transferencias y approvals
+#[contractevent(topics = ["paused", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Paused {
+    pub caller: Address,
+}
+
+/// Se reanudó el token
+#[contractevent(topics = ["unpaused", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Unpaused {
+    pub caller: Address,
+}
+
+/// El admin configuró la expiración de los vintages (`None` = no vencen)
+#[contractevent(topics = ["vintage_expiry_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VintageExpirySet {
+    pub expiry_vintages: Option<u32>,
+}
+
+/// El admin quemó el balance vencido de un holder
+#[contractevent(topics = ["expired_swept", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExpiredSwept {
+    #[topic]
+    pub holder: Address,
+    pub amount: i128,
+}
+
+/// El código del token fue actualizado
+#[contractevent(topics = ["upgraded", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Upgraded {
+    pub new_wasm_hash: BytesN<32>,
+}
+
+/// El almacenamiento fue migrado a una nueva versión de esquema
+#[contractevent(topics = ["migrated", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Migrated {
+    pub from_version: u32,
+    pub to_version: u32,
+}
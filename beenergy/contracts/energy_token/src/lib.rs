@@ -5,13 +5,99 @@
 //! Token fungible SEP-41 para representar kWh de energía solar.
 //! - 1 token = 1 kWh de energía
 //! - Minteo: Solo por cuentas autorizadas (contratos de distribución)
-//! - Quema: Cuando se consume energía
+//! - Cuotas por minter: máximo por ventana de ledgers y máximo histórico (ver `quota`)
+//! - Quema: Cuando se consume energía (incluye la liquidación commit-reveal)
 //! - Compatible con Stellar DEX para trading P2P
+//! - Vintages: el balance se guarda por mes de generación y vence según la
+//!   expiración configurada (ver `vintage`)
+//! - Pausa de emergencia (`PAUSER_ROLE`) de minteo, quema, liquidaciones,
+//!   transferencias y approvals
 
-use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env, String};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, panic_with_error, symbol_short, Address,
+    BytesN, Env, Map, String, Symbol, Vec,
+};
 use stellar_access::access_control::{self as access_control, AccessControl};
-use stellar_macros::{default_impl, only_role};
-use stellar_tokens::fungible::{burnable::FungibleBurnable, Base, FungibleToken};
+use stellar_macros::default_impl;
+use stellar_tokens::fungible::{
+    burnable::{self, FungibleBurnable},
+    Base, FungibleToken,
+};
+
+pub mod events;
+mod quota;
+mod storage;
+mod vintage;
+
+pub use quota::{MinterQuota, MinterUsage, QuotaLimit};
+pub use vintage::{VintageBalance, VINTAGE_SECONDS};
+
+/// Errores del token de energía
+///
+/// Los códigos son estables: el frontend los mapea a mensajes (ver
+/// FRONTEND_ERROR_HANDLING.md). Las funciones SEP-41 estándar (transfer,
+/// approve, ...) siguen usando los errores de OpenZeppelin, salvo `Paused`.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum TokenError {
+    /// El contrato no tiene admin configurado
+    AdminNotSet = 1,
+    /// El llamador no tiene el rol de minter
+    UnauthorizedMinter = 2,
+    /// La cantidad debe ser mayor que cero
+    InvalidAmount = 3,
+    /// El resultado no entra en un i128 (supply total)
+    ArithmeticOverflow = 4,
+    /// El token está pausado
+    Paused = 5,
+    /// El balance no alcanza para la quema
+    InsufficientBalance = 6,
+    /// El almacenamiento es de una versión de esquema más nueva que el código
+    UnsupportedSchemaVersion = 7,
+    /// El minteo supera la cuota del minter (por ventana o histórica)
+    MinterQuotaExceeded = 8,
+    /// Cuota inválida (ventana en 0 o límites no positivos)
+    InvalidQuota = 9,
+    /// El llamador no tiene el rol de pauser
+    UnauthorizedPauser = 10,
+    /// El balance alcanza, pero parte está vencido
+    BalanceExpired = 11,
+    /// La expiración debe ser de al menos un vintage
+    InvalidExpiry = 12,
+    /// El llamador no tiene el rol de liquidación
+    UnauthorizedSettler = 13,
+    /// El total histórico minteado por el minter no entra en un i128
+    MinterUsageOverflow = 14,
+}
+
+/// Rol que puede mintear (y liquidar consumo)
+pub const MINTER_ROLE: Symbol = symbol_short!("minter");
+
+/// Rol que puede quemar consumo liquidado sin la firma del holder
+/// (solo el contrato de distribución)
+pub const SETTLEMENT_ROLE: Symbol = symbol_short!("settle");
+
+/// Rol que puede pausar y reanudar el token
+pub const PAUSER_ROLE: Symbol = symbol_short!("pauser");
+
+/// Versión del esquema de almacenamiento que escribe este código
+///
+/// Se incrementa cuando un upgrade cambia el layout del almacenamiento y
+/// `migrate` necesita convertir los datos existentes.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Claves de almacenamiento propias (los balances y roles los guarda OpenZeppelin)
+#[contracttype]
+pub enum DataKey {
+    SchemaVersion,
+    Paused,
+    MinterQuota(Address), // Persistente: límites de minteo de un minter
+    MinterUsage(Address), // Persistente: lo minteado por un minter
+    Vintages(Address),    // Persistente: balance de un holder por vintage
+    VintageExpiry,        // Vintages que dura el balance (sin expiración si no está)
+    LegacyVintage,        // Vintage del balance anterior a los vintages (ver `migrate`)
+}
 
 #[contract]
 pub struct EnergyToken;
@@ -41,30 +127,50 @@ impl EnergyToken {
         // Configurar admin del sistema de control de acceso
         access_control::set_admin(e, &admin);
 
-        // Otorgar rol de MINTER al contrato de distribución
-        access_control::grant_role_no_auth(
-            e,
-            &admin,
-            &distribution_contract,
-            &symbol_short!("minter"),
-        );
+        // Otorgar roles de MINTER y de liquidación al contrato de distribución
+        access_control::grant_role_no_auth(e, &admin, &distribution_contract, &MINTER_ROLE);
+        access_control::grant_role_no_auth(e, &admin, &distribution_contract, &SETTLEMENT_ROLE);
+
+        // El admin puede pausar el token desde el despliegue
+        access_control::grant_role_no_auth(e, &admin, &admin, &PAUSER_ROLE);
 
         // Mintear supply inicial al admin si es mayor que 0
         if initial_supply > 0 {
-            Base::mint(e, &admin, initial_supply);
+            Self::mint_vintage(e, &admin, initial_supply);
         }
+
+        e.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
     }
 
     /// Mintea tokens cuando se genera energía
     /// Solo puede ser llamado por cuentas con rol MINTER
     ///
+    /// Si el minter tiene cuota, el minteo no puede superarla; el minteo que
+    /// agota un límite publica `MinterQuotaExhausted`.
+    ///
     /// # Argumentos
     /// * `to` - Dirección que recibirá los tokens
     /// * `amount` - Cantidad de kWh (tokens) a mintear
     /// * `minter` - Dirección que está minteando (debe tener rol MINTER)
-    #[only_role(minter, "minter")]
-    pub fn mint_energy(e: &Env, to: Address, amount: i128, minter: Address) {
-        Base::mint(e, &to, amount);
+    pub fn mint_energy(
+        e: &Env,
+        to: Address,
+        amount: i128,
+        minter: Address,
+    ) -> Result<(), TokenError> {
+        Self::require_minter(e, &minter)?;
+        Self::require_not_paused(e)?;
+        Self::require_positive(amount)?;
+
+        if Base::total_supply(e).checked_add(amount).is_none() {
+            return Err(TokenError::ArithmeticOverflow);
+        }
+        Self::consume_quota(e, &minter, amount)?;
+        Self::mint_vintage(e, &to, amount);
+
+        Ok(())
     }
 
     /// Quema tokens cuando se consume energía
@@ -72,9 +178,59 @@ impl EnergyToken {
     /// # Argumentos
     /// * `from` - Dirección de la que se quemarán tokens
     /// * `amount` - Cantidad de kWh (tokens) a quemar
-    pub fn burn_energy(e: &Env, from: Address, amount: i128) {
+    pub fn burn_energy(e: &Env, from: Address, amount: i128) -> Result<(), TokenError> {
+        Self::require_not_paused(e)?;
+        Self::require_positive(amount)?;
+        Self::require_balance(e, &from, amount)?;
+        Self::spend_vintages(e, &from, amount)?;
+
         // Base::burn ya maneja la autenticación internamente
         Base::burn(e, &from, amount);
+
+        Ok(())
+    }
+
+    /// Quema el consumo liquidado por el contrato de distribución
+    /// Solo puede ser llamado por cuentas con rol de liquidación
+    ///
+    /// A diferencia de `burn_energy`, no requiere la firma de `from`: el
+    /// contrato de distribución ya verificó el consumo revelado (o penaliza
+    /// un consumo que no se reveló a tiempo). Por eso usa un rol propio y no
+    /// el de minter: los minters (medidores, otros contratos) no pueden
+    /// quemar balances ajenos. El balance vencido no lo bloquea (se quema al
+    /// final), para que una liquidación no falle por vencimiento.
+    ///
+    /// # Argumentos
+    /// * `from` - Dirección de la que se quemarán tokens
+    /// * `amount` - Cantidad de kWh (tokens) a quemar
+    /// * `settler` - Dirección que liquida el consumo (debe tener rol de liquidación)
+    pub fn burn_consumption(
+        e: &Env,
+        from: Address,
+        amount: i128,
+        settler: Address,
+    ) -> Result<(), TokenError> {
+        Self::require_settler(e, &settler)?;
+        Self::require_not_paused(e)?;
+        Self::require_positive(amount)?;
+        Self::require_balance(e, &from, amount)?;
+
+        let expiry = Self::get_vintage_expiry(e);
+        let current = vintage::current(e);
+        let mut buckets = Self::load_vintages(e, &from);
+        let usable = vintage::take(e, &mut buckets, amount, |v| {
+            !vintage::is_expired(v, current, expiry)
+        });
+        let rest = amount - vintage::total(&usable, |_| true);
+        vintage::take(e, &mut buckets, rest, |_| true);
+        Self::save_vintages(e, &from, &buckets);
+
+        // `Base::burn` exige la firma de `from`: se actualiza el balance
+        // directamente y se publica el mismo evento SEP-41 `burn`
+        Base::update(e, Some(&from), None, amount);
+        burnable::emit_burn(e, &from, amount);
+
+        Ok(())
     }
 
     /// Otorga rol de minter a una nueva dirección
@@ -82,10 +238,10 @@ impl EnergyToken {
     ///
     /// # Argumentos
     /// * `new_minter` - Dirección que recibirá el rol de minter
-    pub fn grant_minter(e: &Env, new_minter: Address) {
-        let admin = access_control::get_admin(e).expect("admin not set");
-        admin.require_auth();
-        access_control::grant_role_no_auth(e, &admin, &new_minter, &symbol_short!("minter"));
+    pub fn grant_minter(e: &Env, new_minter: Address) -> Result<(), TokenError> {
+        let admin = Self::require_admin(e)?;
+        access_control::grant_role_no_auth(e, &admin, &new_minter, &MINTER_ROLE);
+        Ok(())
     }
 
     /// Revoca rol de minter de una dirección
@@ -93,20 +249,479 @@ impl EnergyToken {
     ///
     /// # Argumentos
     /// * `minter` - Dirección a la que se le revocará el rol
-    pub fn revoke_minter(e: &Env, minter: Address) {
-        let admin = access_control::get_admin(e).expect("admin not set");
-        admin.require_auth();
-        access_control::revoke_role_no_auth(e, &admin, &minter, &symbol_short!("minter"));
+    pub fn revoke_minter(e: &Env, minter: Address) -> Result<(), TokenError> {
+        let admin = Self::require_admin(e)?;
+        access_control::revoke_role_no_auth(e, &admin, &minter, &MINTER_ROLE);
+        Ok(())
+    }
+
+    /// Configura la cuota de minteo de un minter
+    /// Solo puede ser llamado por el admin
+    ///
+    /// Lo ya minteado se mantiene: bajar la cuota por debajo de lo usado deja
+    /// al minter sin margen hasta la próxima ventana (o para siempre, si es
+    /// el límite histórico). Revocar el rol tampoco borra lo usado.
+    ///
+    /// # Argumentos
+    /// * `minter` - Dirección con (o que tendrá) rol de minter
+    /// * `quota` - Ventana en ledgers, máximo por ventana y máximo histórico
+    pub fn set_minter_quota(
+        e: &Env,
+        minter: Address,
+        quota: MinterQuota,
+    ) -> Result<(), TokenError> {
+        Self::require_admin(e)?;
+
+        if !quota.is_valid() {
+            return Err(TokenError::InvalidQuota);
+        }
+        storage::set(e, &DataKey::MinterQuota(minter.clone()), &quota);
+
+        events::MinterQuotaSet { minter, quota }.publish(e);
+
+        Ok(())
+    }
+
+    /// Cuota de un minter (`None` si no tiene límite)
+    pub fn get_minter_quota(e: &Env, minter: Address) -> Option<MinterQuota> {
+        storage::get(e, &DataKey::MinterQuota(minter))
+    }
+
+    /// Lo minteado por un minter, con la ventana vigente en este ledger
+    pub fn get_minter_usage(e: &Env, minter: Address) -> MinterUsage {
+        let usage: MinterUsage =
+            storage::get(e, &DataKey::MinterUsage(minter.clone())).unwrap_or_default();
+
+        match Self::get_minter_quota(e, minter) {
+            Some(quota) => quota.current_usage(&usage, e.ledger().sequence()),
+            None => usage,
+        }
+    }
+
+    /// Cuánto puede mintear todavía un minter en este ledger (`None` si no tiene límite)
+    pub fn get_mint_allowance(e: &Env, minter: Address) -> Option<i128> {
+        let quota = Self::get_minter_quota(e, minter.clone())?;
+        Some(quota.remaining(&Self::get_minter_usage(e, minter)))
+    }
+
+    /// Configura cuántos vintages dura el balance antes de vencer
+    /// Solo puede ser llamado por el admin
+    ///
+    /// Con `Some(12)`, lo minteado en un mes vence doce meses después. `None`
+    /// quita la expiración. Aplica también al balance ya minteado.
+    ///
+    /// # Argumentos
+    /// * `expiry_vintages` - Vintages de vida del balance (`None` = no vence)
+    pub fn set_vintage_expiry(e: &Env, expiry_vintages: Option<u32>) -> Result<(), TokenError> {
+        Self::require_admin(e)?;
+
+        match expiry_vintages {
+            Some(0) => return Err(TokenError::InvalidExpiry),
+            Some(expiry) => e.storage().instance().set(&DataKey::VintageExpiry, &expiry),
+            None => e.storage().instance().remove(&DataKey::VintageExpiry),
+        }
+
+        events::VintageExpirySet { expiry_vintages }.publish(e);
+
+        Ok(())
+    }
+
+    /// Vintages que dura el balance (`None` si no vence)
+    pub fn get_vintage_expiry(e: &Env) -> Option<u32> {
+        e.storage().instance().get(&DataKey::VintageExpiry)
+    }
+
+    /// Vintage en el que se asigna lo que se mintea en este ledger
+    pub fn get_current_vintage(e: &Env) -> u32 {
+        vintage::current(e)
+    }
+
+    /// Balance de un holder por vintage, del más viejo al más nuevo
+    pub fn get_vintage_balances(e: &Env, holder: Address) -> Vec<VintageBalance> {
+        let expiry = Self::get_vintage_expiry(e);
+        let current = vintage::current(e);
+
+        let mut balances = Vec::new(e);
+        for (vintage, amount) in Self::load_vintages(e, &holder).iter() {
+            balances.push_back(VintageBalance {
+                vintage,
+                amount,
+                expired: vintage::is_expired(vintage, current, expiry),
+            });
+        }
+        balances
+    }
+
+    /// Balance de un holder que todavía no venció
+    pub fn usable_balance(e: &Env, holder: Address) -> i128 {
+        let expiry = Self::get_vintage_expiry(e);
+        let current = vintage::current(e);
+        vintage::total(&Self::load_vintages(e, &holder), |v| {
+            !vintage::is_expired(v, current, expiry)
+        })
+    }
+
+    /// Quema el balance vencido de un holder
+    /// Solo puede ser llamado por el admin
+    ///
+    /// Publica el evento SEP-41 `burn` (para indexadores y wallets) y
+    /// `ExpiredSwept`.
+    ///
+    /// # Argumentos
+    /// * `holder` - Dirección cuyo balance vencido se quema
+    ///
+    /// # Retorna
+    /// La cantidad quemada
+    pub fn sweep_expired(e: &Env, holder: Address) -> Result<i128, TokenError> {
+        Self::require_admin(e)?;
+        Self::require_not_paused(e)?;
+
+        let expiry = Self::get_vintage_expiry(e);
+        let current = vintage::current(e);
+        let mut buckets = Self::load_vintages(e, &holder);
+        let expired = vintage::take(e, &mut buckets, i128::MAX, |v| {
+            vintage::is_expired(v, current, expiry)
+        });
+        let amount = vintage::total(&expired, |_| true);
+        if amount == 0 {
+            return Ok(0);
+        }
+
+        Self::save_vintages(e, &holder, &buckets);
+        Base::update(e, Some(&holder), None, amount);
+        burnable::emit_burn(e, &holder, amount);
+
+        events::ExpiredSwept { holder, amount }.publish(e);
+
+        Ok(amount)
+    }
+
+    /// Pausa minteo, quema (incluida la de liquidaciones), transferencias y
+    /// approvals
+    /// Solo puede ser llamado por cuentas con rol PAUSER
+    ///
+    /// Las consultas siguen funcionando.
+    ///
+    /// # Argumentos
+    /// * `caller` - Dirección que pausa (debe tener rol PAUSER)
+    pub fn pause(e: &Env, caller: Address) -> Result<(), TokenError> {
+        Self::require_pauser(e, &caller)?;
+
+        e.storage().instance().set(&DataKey::Paused, &true);
+
+        events::Paused { caller }.publish(e);
+
+        Ok(())
+    }
+
+    /// Reanuda el token
+    /// Solo puede ser llamado por cuentas con rol PAUSER
+    ///
+    /// # Argumentos
+    /// * `caller` - Dirección que reanuda (debe tener rol PAUSER)
+    pub fn unpause(e: &Env, caller: Address) -> Result<(), TokenError> {
+        Self::require_pauser(e, &caller)?;
+
+        e.storage().instance().set(&DataKey::Paused, &false);
+
+        events::Unpaused { caller }.publish(e);
+
+        Ok(())
+    }
+
+    /// Verifica si el token está pausado
+    pub fn is_paused(e: &Env) -> bool {
+        e.storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false)
     }
 
     /// Verifica si una dirección tiene rol de minter
     pub fn is_minter(e: &Env, account: Address) -> bool {
-        access_control::has_role(e, &account, &symbol_short!("minter")).is_some()
+        access_control::has_role(e, &account, &MINTER_ROLE).is_some()
     }
 
     /// Obtiene el admin actual
-    pub fn admin(e: &Env) -> Address {
-        access_control::get_admin(e).expect("admin not set")
+    pub fn admin(e: &Env) -> Result<Address, TokenError> {
+        access_control::get_admin(e).ok_or(TokenError::AdminNotSet)
+    }
+
+    /// Actualiza el código del contrato sin perder balances ni roles
+    /// Solo puede ser llamado por el admin
+    ///
+    /// Después del upgrade hay que invocar `migrate` para convertir el
+    /// almacenamiento si cambió la versión de esquema.
+    ///
+    /// # Argumentos
+    /// * `new_wasm_hash` - Hash del wasm ya instalado en la red
+    pub fn upgrade(e: &Env, new_wasm_hash: BytesN<32>) -> Result<(), TokenError> {
+        Self::require_admin(e)?;
+        e.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+
+        events::Upgraded { new_wasm_hash }.publish(e);
+
+        Ok(())
+    }
+
+    /// Migra el almacenamiento a `SCHEMA_VERSION`
+    /// Solo puede ser llamado por el admin
+    ///
+    /// Es idempotente: si el esquema ya está al día no modifica nada.
+    ///
+    /// # Retorna
+    /// La versión de esquema resultante (igual que `EnergyDistribution::migrate`)
+    pub fn migrate(e: &Env) -> Result<u32, TokenError> {
+        Self::require_admin(e)?;
+
+        let from_version = Self::get_schema_version(e);
+        if from_version > SCHEMA_VERSION {
+            return Err(TokenError::UnsupportedSchemaVersion);
+        }
+
+        // v1 -> v2: el balance existente no tiene vintages; se le asigna
+        // el vintage actual (vence como si se hubiera minteado hoy)
+        if from_version < 2 && !e.storage().instance().has(&DataKey::LegacyVintage) {
+            e.storage()
+                .instance()
+                .set(&DataKey::LegacyVintage, &vintage::current(e));
+        }
+
+        e.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+
+        if from_version < SCHEMA_VERSION {
+            events::Migrated {
+                from_version,
+                to_version: SCHEMA_VERSION,
+            }
+            .publish(e);
+        }
+
+        Ok(SCHEMA_VERSION)
+    }
+
+    /// Versión del esquema de almacenamiento (1 si nunca fue registrada)
+    pub fn get_schema_version(e: &Env) -> u32 {
+        e.storage()
+            .instance()
+            .get(&DataKey::SchemaVersion)
+            .unwrap_or(1)
+    }
+
+    // ========================================================================
+    // Helpers
+    // ========================================================================
+
+    /// Verifica la firma del admin y lo devuelve
+    fn require_admin(e: &Env) -> Result<Address, TokenError> {
+        let admin = access_control::get_admin(e).ok_or(TokenError::AdminNotSet)?;
+        admin.require_auth();
+        Ok(admin)
+    }
+
+    /// Verifica que `minter` firmó la invocación y tiene `MINTER_ROLE`
+    fn require_minter(e: &Env, minter: &Address) -> Result<(), TokenError> {
+        minter.require_auth();
+
+        if access_control::has_role(e, minter, &MINTER_ROLE).is_none() {
+            return Err(TokenError::UnauthorizedMinter);
+        }
+
+        Ok(())
+    }
+
+    /// Verifica que `settler` firmó la invocación y tiene `SETTLEMENT_ROLE`
+    fn require_settler(e: &Env, settler: &Address) -> Result<(), TokenError> {
+        settler.require_auth();
+
+        if access_control::has_role(e, settler, &SETTLEMENT_ROLE).is_none() {
+            return Err(TokenError::UnauthorizedSettler);
+        }
+
+        Ok(())
+    }
+
+    /// Verifica que `caller` firmó la invocación y tiene `PAUSER_ROLE`
+    fn require_pauser(e: &Env, caller: &Address) -> Result<(), TokenError> {
+        caller.require_auth();
+
+        if access_control::has_role(e, caller, &PAUSER_ROLE).is_none() {
+            return Err(TokenError::UnauthorizedPauser);
+        }
+
+        Ok(())
+    }
+
+    /// Falla con `Paused` si el token está pausado
+    fn require_not_paused(e: &Env) -> Result<(), TokenError> {
+        if Self::is_paused(e) {
+            return Err(TokenError::Paused);
+        }
+        Ok(())
+    }
+
+    /// Entra en pánico con `Paused` si el token está pausado
+    ///
+    /// Para las funciones SEP-41, cuya firma no devuelve `Result`.
+    fn panic_if_paused(e: &Env) {
+        if Self::is_paused(e) {
+            panic_with_error!(e, TokenError::Paused);
+        }
+    }
+
+    /// Entra en pánico con el error de `result`, si tiene
+    ///
+    /// Para las funciones SEP-41, cuya firma no devuelve `Result`.
+    fn panic_on_error<T>(e: &Env, result: Result<T, TokenError>) {
+        if let Err(error) = result {
+            panic_with_error!(e, error);
+        }
+    }
+
+    /// Descuenta `amount` de la cuota del minter, si tiene
+    ///
+    /// El uso se registra aunque no haya cuota, así una cuota configurada
+    /// después cuenta lo minteado hasta entonces.
+    fn consume_quota(e: &Env, minter: &Address, amount: i128) -> Result<(), TokenError> {
+        let mut usage = Self::get_minter_usage(e, minter.clone());
+        let quota = Self::get_minter_quota(e, minter.clone());
+
+        if let Some(quota) = &quota {
+            if amount > quota.remaining(&usage) {
+                return Err(TokenError::MinterQuotaExceeded);
+            }
+            // Sin cuota no hay ventana: solo se acumula el histórico
+            usage.window_minted += amount;
+        }
+        usage.lifetime_minted = usage
+            .lifetime_minted
+            .checked_add(amount)
+            .ok_or(TokenError::MinterUsageOverflow)?;
+        storage::set(e, &DataKey::MinterUsage(minter.clone()), &usage);
+
+        if let Some(quota) = quota {
+            for limit in quota.exhausted(&usage).into_iter().flatten() {
+                events::MinterQuotaExhausted {
+                    minter: minter.clone(),
+                    limit,
+                    window_start: usage.window_start,
+                }
+                .publish(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Mintea `amount` a `to` en el vintage actual
+    fn mint_vintage(e: &Env, to: &Address, amount: i128) {
+        let mut buckets = Self::load_vintages(e, to);
+        let current = vintage::current(e);
+        buckets.set(current, buckets.get(current).unwrap_or(0) + amount);
+        Self::save_vintages(e, to, &buckets);
+
+        Base::mint(e, to, amount);
+    }
+
+    /// Mueve los vintages de una transferencia de `from` a `to`
+    ///
+    /// Se llama antes de mover el balance en `Base`, que es el que usa
+    /// `load_vintages` para detectar balance sin vintage.
+    fn transfer_vintages(
+        e: &Env,
+        from: &Address,
+        to: &Address,
+        amount: i128,
+    ) -> Result<(), TokenError> {
+        // Se lee antes de sacar de `from`: si son la misma dirección, sus
+        // vintages quedan como estaban
+        let mut to_buckets = Self::load_vintages(e, to);
+        let moved = Self::spend_vintages(e, from, amount)?;
+        if from != to {
+            vintage::add(&mut to_buckets, &moved);
+        }
+        Self::save_vintages(e, to, &to_buckets);
+        Ok(())
+    }
+
+    /// Saca `amount` de los vintages sin vencer de `from`, del más viejo al
+    /// más nuevo, y devuelve lo sacado por vintage
+    ///
+    /// Las cantidades no positivas no mueven vintages (`Base` las rechaza).
+    fn spend_vintages(e: &Env, from: &Address, amount: i128) -> Result<Map<u32, i128>, TokenError> {
+        if amount <= 0 {
+            return Ok(Map::new(e));
+        }
+
+        let expiry = Self::get_vintage_expiry(e);
+        let current = vintage::current(e);
+        let mut buckets = Self::load_vintages(e, from);
+        let usable = vintage::total(&buckets, |v| !vintage::is_expired(v, current, expiry));
+        if usable < amount {
+            return Err(if Base::balance(e, from) >= amount {
+                TokenError::BalanceExpired
+            } else {
+                TokenError::InsufficientBalance
+            });
+        }
+
+        let taken = vintage::take(e, &mut buckets, amount, |v| {
+            !vintage::is_expired(v, current, expiry)
+        });
+        Self::save_vintages(e, from, &buckets);
+        Ok(taken)
+    }
+
+    /// Vintages de un holder
+    ///
+    /// El balance que no está en ningún vintage (minteado antes de los
+    /// vintages) se asigna a `LegacyVintage`, o al vintage actual si todavía
+    /// no se migró.
+    fn load_vintages(e: &Env, holder: &Address) -> Map<u32, i128> {
+        let mut buckets: Map<u32, i128> =
+            storage::get(e, &DataKey::Vintages(holder.clone())).unwrap_or_else(|| Map::new(e));
+
+        let legacy = Base::balance(e, holder) - vintage::total(&buckets, |_| true);
+        if legacy > 0 {
+            let legacy_vintage = e
+                .storage()
+                .instance()
+                .get(&DataKey::LegacyVintage)
+                .unwrap_or_else(|| vintage::current(e));
+            buckets.set(
+                legacy_vintage,
+                buckets.get(legacy_vintage).unwrap_or(0) + legacy,
+            );
+        }
+        buckets
+    }
+
+    fn save_vintages(e: &Env, holder: &Address, buckets: &Map<u32, i128>) {
+        let key = DataKey::Vintages(holder.clone());
+        if buckets.is_empty() {
+            storage::remove(e, &key);
+        } else {
+            storage::set(e, &key, buckets);
+        }
+    }
+
+    /// Rechaza cantidades cero o negativas
+    fn require_positive(amount: i128) -> Result<(), TokenError> {
+        if amount <= 0 {
+            return Err(TokenError::InvalidAmount);
+        }
+        Ok(())
+    }
+
+    /// Verifica que `from` tenga al menos `amount` para quemar
+    fn require_balance(e: &Env, from: &Address, amount: i128) -> Result<(), TokenError> {
+        if Base::balance(e, from) < amount {
+            return Err(TokenError::InsufficientBalance);
+        }
+        Ok(())
     }
 }
 
@@ -115,16 +730,49 @@ impl EnergyToken {
 // ============================================================================
 
 /// Implementa funciones estándar SEP-41 (transfer, balance, approve, etc.)
+///
+/// Las que mueven balances o allowances fallan con `Paused` si el token está
+/// pausado, y las que mueven balances, con `BalanceExpired` si solo alcanza
+/// con balance vencido; el resto usa la implementación por defecto.
 #[default_impl]
 #[contractimpl]
 impl FungibleToken for EnergyToken {
     type ContractType = Base;
+
+    fn transfer(e: &Env, from: Address, to: Address, amount: i128) {
+        Self::panic_if_paused(e);
+        Self::panic_on_error(e, Self::transfer_vintages(e, &from, &to, amount));
+        Base::transfer(e, &from, &to, amount);
+    }
+
+    fn transfer_from(e: &Env, spender: Address, from: Address, to: Address, amount: i128) {
+        Self::panic_if_paused(e);
+        Self::panic_on_error(e, Self::transfer_vintages(e, &from, &to, amount));
+        Base::transfer_from(e, &spender, &from, &to, amount);
+    }
+
+    fn approve(e: &Env, owner: Address, spender: Address, amount: i128, live_until_ledger: u32) {
+        Self::panic_if_paused(e);
+        Base::approve(e, &owner, &spender, amount, live_until_ledger);
+    }
 }
 
-/// Implementa funciones de quema
+/// Implementa funciones de quema (bloqueadas con el token pausado)
 #[default_impl]
 #[contractimpl]
-impl FungibleBurnable for EnergyToken {}
+impl FungibleBurnable for EnergyToken {
+    fn burn(e: &Env, from: Address, amount: i128) {
+        Self::panic_if_paused(e);
+        Self::panic_on_error(e, Self::spend_vintages(e, &from, amount));
+        Base::burn(e, &from, amount);
+    }
+
+    fn burn_from(e: &Env, spender: Address, from: Address, amount: i128) {
+        Self::panic_if_paused(e);
+        Self::panic_on_error(e, Self::spend_vintages(e, &from, amount));
+        Base::burn_from(e, &spender, &from, amount);
+    }
+}
 
 /// Implementa sistema de control de acceso
 #[default_impl]
@@ -138,7 +786,11 @@ impl AccessControl for EnergyToken {}
 #[cfg(test)]
 mod test {
     use super::*;
-    use soroban_sdk::{testutils::Address as _, Env};
+    use proptest::prelude::*;
+    use soroban_sdk::{
+        testutils::{storage::Persistent as _, Address as _, Events, Ledger},
+        vec, Env, Event as _,
+    };
 
     #[test]
     fn test_initialize() {
@@ -157,6 +809,7 @@ mod test {
         assert_eq!(client.total_supply(), 0);
         assert_eq!(client.admin(), admin);
         assert!(client.is_minter(&distribution));
+        assert_eq!(client.get_schema_version(), SCHEMA_VERSION);
     }
 
     #[test]
@@ -199,6 +852,48 @@ mod test {
         assert_eq!(client.total_supply(), 70_0000000);
     }
 
+    #[test]
+    fn test_burn_consumption_requires_settlement_role() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let distribution = Address::generate(&env);
+        let meter = Address::generate(&env);
+        let user = Address::generate(&env);
+
+        let contract_id = env.register(EnergyToken, (&admin, &distribution, &0i128));
+        let client = EnergyTokenClient::new(&env, &contract_id);
+        client.grant_minter(&meter);
+
+        client.mint_energy(&user, &100_0000000, &distribution);
+
+        // Ni un minter ni cualquier otra cuenta pueden quemar balances ajenos
+        assert_eq!(
+            client.try_burn_consumption(&user, &10_0000000, &meter),
+            Err(Ok(TokenError::UnauthorizedSettler))
+        );
+        assert_eq!(
+            client.try_burn_consumption(&user, &10_0000000, &user),
+            Err(Ok(TokenError::UnauthorizedSettler))
+        );
+        assert!(client.has_role(&distribution, &SETTLEMENT_ROLE).is_some());
+        assert!(client.has_role(&meter, &SETTLEMENT_ROLE).is_none());
+
+        client.burn_consumption(&user, &30_0000000, &distribution);
+        let burn = burnable::Burn {
+            from: user.clone(),
+            amount: 30_0000000,
+        };
+        assert!(env
+            .events()
+            .all()
+            .events()
+            .contains(&burn.to_xdr(&env, &contract_id)));
+        assert_eq!(client.balance(&user), 70_0000000);
+        assert_eq!(client.total_supply(), 70_0000000);
+    }
+
     #[test]
     fn test_transfer_between_users() {
         let env = Env::default();
@@ -242,6 +937,128 @@ mod test {
         assert!(!client.is_minter(&new_minter));
     }
 
+    #[test]
+    fn test_minter_quota_window_and_lifetime() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let distribution = Address::generate(&env);
+        let user = Address::generate(&env);
+
+        let contract_id = env.register(EnergyToken, (&admin, &distribution, &0i128));
+        let client = EnergyTokenClient::new(&env, &contract_id);
+
+        // Sin cuota no hay límite
+        assert_eq!(client.get_mint_allowance(&distribution), None);
+        client.mint_energy(&user, &30, &distribution);
+
+        let invalid = MinterQuota {
+            window_ledgers: 0,
+            window_limit: 100,
+            lifetime_limit: 250,
+        };
+        assert_eq!(
+            client.try_set_minter_quota(&distribution, &invalid),
+            Err(Ok(TokenError::InvalidQuota))
+        );
+
+        let quota = MinterQuota {
+            window_ledgers: 10,
+            ..invalid
+        };
+        client.set_minter_quota(&distribution, &quota);
+        assert_eq!(client.get_minter_quota(&distribution), Some(quota));
+
+        // Lo minteado antes de la cuota cuenta para el histórico
+        assert_eq!(client.get_minter_usage(&distribution).lifetime_minted, 30);
+        assert_eq!(client.get_mint_allowance(&distribution), Some(100));
+
+        // Límite por ventana
+        client.mint_energy(&user, &60, &distribution);
+        assert_eq!(
+            client.try_mint_energy(&user, &41, &distribution),
+            Err(Ok(TokenError::MinterQuotaExceeded))
+        );
+        client.mint_energy(&user, &40, &distribution);
+        let exhausted = events::MinterQuotaExhausted {
+            minter: distribution.clone(),
+            limit: QuotaLimit::Window,
+            window_start: env.ledger().sequence(),
+        };
+        assert!(env
+            .events()
+            .all()
+            .events()
+            .contains(&exhausted.to_xdr(&env, &contract_id)));
+        assert_eq!(client.get_mint_allowance(&distribution), Some(0));
+
+        // La ventana siguiente vuelve a tener margen, hasta el límite histórico
+        env.ledger().with_mut(|li| li.sequence_number += 10);
+        assert_eq!(client.get_mint_allowance(&distribution), Some(100));
+        client.mint_energy(&user, &100, &distribution);
+        env.ledger().with_mut(|li| li.sequence_number += 10);
+        assert_eq!(client.get_mint_allowance(&distribution), Some(20));
+        client.mint_energy(&user, &20, &distribution);
+        let exhausted = events::MinterQuotaExhausted {
+            minter: distribution.clone(),
+            limit: QuotaLimit::Lifetime,
+            window_start: env.ledger().sequence(),
+        };
+        assert!(env
+            .events()
+            .all()
+            .events()
+            .contains(&exhausted.to_xdr(&env, &contract_id)));
+
+        env.ledger().with_mut(|li| li.sequence_number += 10);
+        assert_eq!(client.get_mint_allowance(&distribution), Some(0));
+        assert_eq!(
+            client.try_mint_energy(&user, &1, &distribution),
+            Err(Ok(TokenError::MinterQuotaExceeded))
+        );
+        assert_eq!(client.balance(&user), 250);
+    }
+
+    #[test]
+    fn test_holder_and_minter_entries_extend_ttl() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let distribution = Address::generate(&env);
+        let user = Address::generate(&env);
+
+        let contract_id = env.register(EnergyToken, (&admin, &distribution, &0i128));
+        let client = EnergyTokenClient::new(&env, &contract_id);
+        let quota = MinterQuota {
+            window_ledgers: 10,
+            window_limit: 100,
+            lifetime_limit: 250,
+        };
+        client.set_minter_quota(&distribution, &quota);
+        client.mint_energy(&user, &30, &distribution);
+
+        let ttl = |key: &DataKey| {
+            env.as_contract(&contract_id, || env.storage().persistent().get_ttl(key))
+        };
+        let keys = [
+            DataKey::Vintages(user.clone()),
+            DataKey::MinterQuota(distribution.clone()),
+            DataKey::MinterUsage(distribution.clone()),
+        ];
+        for key in keys.iter() {
+            assert_eq!(ttl(key), storage::PERSISTENT_TTL_EXTEND_TO);
+        }
+
+        // Leer una entrada cerca del umbral la extiende
+        env.ledger()
+            .with_mut(|li| li.sequence_number += 100 * storage::DAY_IN_LEDGERS);
+        assert_eq!(ttl(&keys[1]), 20 * storage::DAY_IN_LEDGERS);
+        assert_eq!(client.get_minter_quota(&distribution), Some(quota));
+        assert_eq!(ttl(&keys[1]), storage::PERSISTENT_TTL_EXTEND_TO);
+    }
+
     #[test]
     fn test_initial_supply() {
         let env = Env::default();
@@ -257,4 +1074,451 @@ mod test {
         assert_eq!(client.balance(&admin), 1000_0000000);
         assert_eq!(client.total_supply(), 1000_0000000);
     }
+
+    #[test]
+    fn test_upgrade_requires_admin() {
+        let env = Env::default();
+
+        let admin = Address::generate(&env);
+        let distribution = Address::generate(&env);
+
+        let contract_id = env.register(EnergyToken, (&admin, &distribution, &0i128));
+        let client = EnergyTokenClient::new(&env, &contract_id);
+        let new_wasm_hash = BytesN::from_array(&env, &[1u8; 32]);
+
+        // Sin la firma del admin
+        assert!(client.try_upgrade(&new_wasm_hash).is_err());
+        assert!(client.try_migrate().is_err());
+    }
+
+    /// WASM del código actual, la versión a la que se actualiza en
+    /// `test_upgrade_to_wasm_and_migrate`
+    ///
+    /// Solo con la feature `upgrade-tests`: hay que compilarlo antes
+    /// (`stellar contract build`) para no probar un WASM viejo.
+    #[cfg(feature = "upgrade-tests")]
+    const WASM: &[u8] = include_bytes!("../../../target/wasm32v1-none/release/energy_token.wasm");
+
+    #[cfg(feature = "upgrade-tests")]
+    #[test]
+    fn test_upgrade_to_wasm_and_migrate() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let distribution = Address::generate(&env);
+        let user = Address::generate(&env);
+
+        let contract_id = env.register(EnergyToken, (&admin, &distribution, &0i128));
+        let client = EnergyTokenClient::new(&env, &contract_id);
+        client.mint_energy(&user, &100_0000000, &distribution);
+
+        // Estado de la versión 1: sin versión de esquema ni vintages
+        env.as_contract(&contract_id, || {
+            env.storage().instance().remove(&DataKey::SchemaVersion);
+            env.storage()
+                .persistent()
+                .remove(&DataKey::Vintages(user.clone()));
+        });
+        env.ledger()
+            .with_mut(|li| li.timestamp = 2 * VINTAGE_SECONDS);
+
+        let new_wasm_hash = env.deployer().upload_contract_wasm(WASM);
+        client.upgrade(&new_wasm_hash);
+        let upgraded = events::Upgraded { new_wasm_hash };
+        assert!(env
+            .events()
+            .all()
+            .events()
+            .contains(&upgraded.to_xdr(&env, &contract_id)));
+
+        // Desde aquí el contrato ejecuta el WASM
+        assert_eq!(client.migrate(), SCHEMA_VERSION);
+        let migrated = events::Migrated {
+            from_version: 1,
+            to_version: SCHEMA_VERSION,
+        };
+        assert!(env
+            .events()
+            .all()
+            .events()
+            .contains(&migrated.to_xdr(&env, &contract_id)));
+        assert_eq!(client.get_schema_version(), SCHEMA_VERSION);
+
+        assert_eq!(client.admin(), admin);
+        assert!(client.is_minter(&distribution));
+        assert_eq!(client.balance(&user), 100_0000000);
+        assert_eq!(client.total_supply(), 100_0000000);
+        assert_eq!(
+            client.get_vintage_balances(&user),
+            vec![
+                &env,
+                VintageBalance {
+                    vintage: 2,
+                    amount: 100_0000000,
+                    expired: false,
+                },
+            ]
+        );
+
+        client.mint_energy(&user, &10_0000000, &distribution);
+        assert_eq!(client.balance(&user), 110_0000000);
+    }
+
+    #[test]
+    fn test_migrate_legacy_token_keeps_balances() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let distribution = Address::generate(&env);
+        let user = Address::generate(&env);
+
+        let contract_id = env.register(EnergyToken, (&admin, &distribution, &0i128));
+        let client = EnergyTokenClient::new(&env, &contract_id);
+        client.mint_energy(&user, &100_0000000, &distribution);
+
+        // Simular un token desplegado antes de registrar la versión de esquema
+        env.as_contract(&contract_id, || {
+            env.storage().instance().remove(&DataKey::SchemaVersion);
+        });
+        assert_eq!(client.get_schema_version(), 1);
+
+        // ... y antes de los vintages
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::Vintages(user.clone()));
+        });
+        env.ledger()
+            .with_mut(|li| li.timestamp = 3 * VINTAGE_SECONDS);
+
+        assert_eq!(client.migrate(), SCHEMA_VERSION);
+        assert_eq!(client.get_schema_version(), SCHEMA_VERSION);
+        assert_eq!(client.balance(&user), 100_0000000);
+
+        // Idempotente: no vuelve a asignar el vintage legacy
+        env.ledger()
+            .with_mut(|li| li.timestamp = 4 * VINTAGE_SECONDS);
+        assert_eq!(client.migrate(), SCHEMA_VERSION);
+        assert!(client.is_minter(&distribution));
+
+        // El balance sin vintage queda en el vintage de la migración
+        env.ledger()
+            .with_mut(|li| li.timestamp = 5 * VINTAGE_SECONDS);
+        assert_eq!(
+            client.get_vintage_balances(&user),
+            vec![
+                &env,
+                VintageBalance {
+                    vintage: 3,
+                    amount: 100_0000000,
+                    expired: false,
+                },
+            ]
+        );
+
+        // Un esquema más nuevo que el código no se puede migrar
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .instance()
+                .set(&DataKey::SchemaVersion, &(SCHEMA_VERSION + 1));
+        });
+        assert_eq!(
+            client.try_migrate(),
+            Err(Ok(TokenError::UnsupportedSchemaVersion))
+        );
+    }
+
+    #[test]
+    fn test_mint_and_burn_errors() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let distribution = Address::generate(&env);
+        let user = Address::generate(&env);
+
+        let contract_id = env.register(EnergyToken, (&admin, &distribution, &0i128));
+        let client = EnergyTokenClient::new(&env, &contract_id);
+
+        // Solo los minters mintean
+        assert_eq!(
+            client.try_mint_energy(&user, &1, &user),
+            Err(Ok(TokenError::UnauthorizedMinter))
+        );
+
+        // No se puede quemar más que el balance
+        client.mint_energy(&user, &10, &distribution);
+        assert_eq!(
+            client.try_burn_energy(&user, &11),
+            Err(Ok(TokenError::InsufficientBalance))
+        );
+        assert_eq!(
+            client.try_burn_consumption(&user, &11, &distribution),
+            Err(Ok(TokenError::InsufficientBalance))
+        );
+
+        // El supply total no puede desbordar
+        assert_eq!(
+            client.try_mint_energy(&user, &i128::MAX, &distribution),
+            Err(Ok(TokenError::ArithmeticOverflow))
+        );
+        assert_eq!(client.total_supply(), 10);
+
+        // Con el supply quemado, el histórico del minter tampoco puede desbordar
+        client.mint_energy(&user, &(i128::MAX - 10), &distribution);
+        client.burn_energy(&user, &i128::MAX);
+        assert_eq!(
+            client.try_mint_energy(&user, &1, &distribution),
+            Err(Ok(TokenError::MinterUsageOverflow))
+        );
+        assert_eq!(client.total_supply(), 0);
+    }
+
+    #[test]
+    fn test_pause_blocks_mint_burn_transfer_and_approve() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let distribution = Address::generate(&env);
+        let user1 = Address::generate(&env);
+        let user2 = Address::generate(&env);
+
+        let contract_id = env.register(EnergyToken, (&admin, &distribution, &0i128));
+        let client = EnergyTokenClient::new(&env, &contract_id);
+        client.mint_energy(&user1, &100_0000000, &distribution);
+
+        // Solo PAUSER_ROLE pausa (el admin lo tiene desde el constructor)
+        assert_eq!(
+            client.try_pause(&user1),
+            Err(Ok(TokenError::UnauthorizedPauser))
+        );
+        client.pause(&admin);
+        let paused_event = events::Paused {
+            caller: admin.clone(),
+        };
+        assert!(env
+            .events()
+            .all()
+            .events()
+            .contains(&paused_event.to_xdr(&env, &contract_id)));
+        assert!(client.is_paused());
+
+        let paused = Err(Ok(soroban_sdk::Error::from_contract_error(
+            TokenError::Paused as u32,
+        )));
+        assert_eq!(
+            client.try_mint_energy(&user1, &1, &distribution),
+            Err(Ok(TokenError::Paused))
+        );
+        assert_eq!(
+            client.try_burn_energy(&user1, &1),
+            Err(Ok(TokenError::Paused))
+        );
+        assert_eq!(client.try_transfer(&user1, &user2, &1), paused);
+        assert_eq!(client.try_approve(&user1, &user2, &1, &1_000), paused);
+        assert_eq!(client.try_transfer_from(&user2, &user1, &user2, &1), paused);
+        assert_eq!(client.try_burn(&user1, &1), paused);
+        assert_eq!(client.try_burn_from(&user2, &user1, &1), paused);
+
+        assert_eq!(
+            client.try_burn_consumption(&user1, &1, &distribution),
+            Err(Ok(TokenError::Paused))
+        );
+
+        // Las consultas siguen funcionando
+        assert_eq!(client.balance(&user1), 100_0000000);
+
+        assert_eq!(
+            client.try_unpause(&user1),
+            Err(Ok(TokenError::UnauthorizedPauser))
+        );
+        client.unpause(&admin);
+        assert!(!client.is_paused());
+
+        client.mint_energy(&user1, &10_0000000, &distribution);
+        client.transfer(&user1, &user2, &20_0000000);
+        client.approve(&user1, &user2, &5_0000000, &1_000);
+        client.transfer_from(&user2, &user1, &user2, &5_0000000);
+        client.burn_energy(&user2, &5_0000000);
+        client.burn_consumption(&user1, &10_0000000, &distribution);
+        assert_eq!(client.balance(&user1), 75_0000000);
+        assert_eq!(client.balance(&user2), 20_0000000);
+    }
+
+    #[test]
+    fn test_vintages_expire_and_can_be_swept() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let distribution = Address::generate(&env);
+        let user1 = Address::generate(&env);
+        let user2 = Address::generate(&env);
+
+        let contract_id = env.register(EnergyToken, (&admin, &distribution, &0i128));
+        let client = EnergyTokenClient::new(&env, &contract_id);
+
+        client.mint_energy(&user1, &100, &distribution);
+        env.ledger().with_mut(|li| li.timestamp = VINTAGE_SECONDS);
+        client.mint_energy(&user1, &50, &distribution);
+        assert_eq!(client.get_current_vintage(), 1);
+
+        assert_eq!(
+            client.try_set_vintage_expiry(&Some(0)),
+            Err(Ok(TokenError::InvalidExpiry))
+        );
+        client.set_vintage_expiry(&Some(2));
+        assert_eq!(client.get_vintage_expiry(), Some(2));
+
+        // La transferencia usa el vintage más viejo y el receptor lo conserva
+        client.transfer(&user1, &user2, &30);
+        let vintage = |vintage, amount, expired| VintageBalance {
+            vintage,
+            amount,
+            expired,
+        };
+        assert_eq!(
+            client.get_vintage_balances(&user1),
+            vec![&env, vintage(0, 70, false), vintage(1, 50, false)]
+        );
+        assert_eq!(
+            client.get_vintage_balances(&user2),
+            vec![&env, vintage(0, 30, false)]
+        );
+
+        // Dos vintages después, el vintage 0 está vencido
+        env.ledger()
+            .with_mut(|li| li.timestamp = 2 * VINTAGE_SECONDS);
+        assert_eq!(
+            client.get_vintage_balances(&user1),
+            vec![&env, vintage(0, 70, true), vintage(1, 50, false)]
+        );
+        assert_eq!(client.balance(&user1), 120);
+        assert_eq!(client.usable_balance(&user1), 50);
+
+        let expired = Err(Ok(soroban_sdk::Error::from_contract_error(
+            TokenError::BalanceExpired as u32,
+        )));
+        assert_eq!(client.try_transfer(&user1, &user2, &60), expired);
+        assert_eq!(client.try_burn(&user1, &60), expired);
+        assert_eq!(
+            client.try_burn_energy(&user1, &60),
+            Err(Ok(TokenError::BalanceExpired))
+        );
+        assert_eq!(
+            client.try_burn_energy(&user1, &200),
+            Err(Ok(TokenError::InsufficientBalance))
+        );
+        client.transfer(&user1, &user2, &10);
+        assert_eq!(client.usable_balance(&user2), 10);
+
+        // El admin barre lo vencido
+        assert_eq!(client.sweep_expired(&user1), 70);
+        let swept = events::ExpiredSwept {
+            holder: user1.clone(),
+            amount: 70,
+        };
+        let burn = burnable::Burn {
+            from: user1.clone(),
+            amount: 70,
+        };
+        let published = env.events().all();
+        assert!(published
+            .events()
+            .contains(&swept.to_xdr(&env, &contract_id)));
+        assert!(published
+            .events()
+            .contains(&burn.to_xdr(&env, &contract_id)));
+        assert_eq!(client.sweep_expired(&user2), 30);
+        assert_eq!(client.sweep_expired(&user2), 0);
+
+        assert_eq!(client.balance(&user1), 40);
+        assert_eq!(client.balance(&user2), 10);
+        assert_eq!(client.total_supply(), 50);
+        assert_eq!(
+            client.get_vintage_balances(&user1),
+            vec![&env, vintage(1, 40, false)]
+        );
+    }
+
+    #[test]
+    fn test_burn_consumption_burns_expired_balance_last() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let distribution = Address::generate(&env);
+        let user = Address::generate(&env);
+
+        let contract_id = env.register(EnergyToken, (&admin, &distribution, &0i128));
+        let client = EnergyTokenClient::new(&env, &contract_id);
+        client.set_vintage_expiry(&Some(1));
+
+        client.mint_energy(&user, &100, &distribution);
+        env.ledger().with_mut(|li| li.timestamp = VINTAGE_SECONDS);
+        client.mint_energy(&user, &50, &distribution);
+
+        // Primero el vintage sin vencer, después el vencido
+        client.burn_consumption(&user, &60, &distribution);
+        assert_eq!(
+            client.get_vintage_balances(&user),
+            vec![
+                &env,
+                VintageBalance {
+                    vintage: 0,
+                    amount: 90,
+                    expired: true,
+                },
+            ]
+        );
+
+        // Sin expiración, todo vuelve a ser usable
+        client.set_vintage_expiry(&None);
+        assert_eq!(client.usable_balance(&user), 90);
+        client.burn_energy(&user, &90);
+        assert_eq!(client.get_vintage_balances(&user), vec![&env]);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_mint_and_burn_reject_non_positive_amounts(
+            amount in prop_oneof![
+                Just(0i128),
+                Just(-1i128),
+                Just(i128::MIN),
+                i128::MIN..=0,
+            ],
+        ) {
+            let env = Env::default();
+            env.mock_all_auths();
+
+            let admin = Address::generate(&env);
+            let distribution = Address::generate(&env);
+            let user = Address::generate(&env);
+
+            let contract_id = env.register(EnergyToken, (&admin, &distribution, &0i128));
+            let client = EnergyTokenClient::new(&env, &contract_id);
+            client.mint_energy(&user, &10, &distribution);
+
+            prop_assert_eq!(
+                client.try_mint_energy(&user, &amount, &distribution),
+                Err(Ok(TokenError::InvalidAmount))
+            );
+            prop_assert_eq!(
+                client.try_burn_energy(&user, &amount),
+                Err(Ok(TokenError::InvalidAmount))
+            );
+            prop_assert_eq!(
+                client.try_burn_consumption(&user, &amount, &distribution),
+                Err(Ok(TokenError::InvalidAmount))
+            );
+            prop_assert_eq!(client.balance(&user), 10);
+            prop_assert_eq!(client.total_supply(), 10);
+        }
+    }
 }
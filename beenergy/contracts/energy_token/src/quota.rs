@@ -0,0 +1,74 @@
+//! # Quota Module - Límites de minteo por minter
+//!
+//! Cada minter puede tener una cuota configurada por el admin:
+//! - `window_limit`: máximo a mintear dentro de una ventana de `window_ledgers` ledgers
+//! - `lifetime_limit`: máximo a mintear en toda la vida del minter
+//!
+//! La ventana es fija: empieza con el primer minteo y, cuando pasan
+//! `window_ledgers` ledgers, el siguiente minteo abre una ventana nueva.
+//! Un minter sin cuota configurada no tiene límite (compatibilidad con los
+//! despliegues anteriores).
+
+use soroban_sdk::contracttype;
+
+/// Límites de minteo de un minter
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MinterQuota {
+    pub window_ledgers: u32,  // Duración de la ventana en ledgers
+    pub window_limit: i128,   // Máximo por ventana
+    pub lifetime_limit: i128, // Máximo histórico
+}
+
+/// Lo minteado por un minter
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MinterUsage {
+    pub window_start: u32,     // Ledger en que empezó la ventana actual
+    pub window_minted: i128,   // Minteado en la ventana actual
+    pub lifetime_minted: i128, // Minteado en total
+}
+
+/// Límite de una cuota
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QuotaLimit {
+    Window,
+    Lifetime,
+}
+
+impl MinterQuota {
+    /// Cuota válida: ventana de al menos un ledger y límites positivos
+    pub fn is_valid(&self) -> bool {
+        self.window_ledgers > 0 && self.window_limit > 0 && self.lifetime_limit > 0
+    }
+
+    /// Uso vigente en `sequence`: si la ventana venció, empieza una nueva
+    pub fn current_usage(&self, usage: &MinterUsage, sequence: u32) -> MinterUsage {
+        let expired = sequence.saturating_sub(usage.window_start) >= self.window_ledgers;
+        if usage.window_minted == 0 || expired {
+            MinterUsage {
+                window_start: sequence,
+                window_minted: 0,
+                lifetime_minted: usage.lifetime_minted,
+            }
+        } else {
+            usage.clone()
+        }
+    }
+
+    /// Cuánto se puede mintear todavía con este uso
+    pub fn remaining(&self, usage: &MinterUsage) -> i128 {
+        let window = self.window_limit - usage.window_minted;
+        let lifetime = self.lifetime_limit - usage.lifetime_minted;
+        window.min(lifetime).max(0)
+    }
+
+    /// Límites que quedan agotados con este uso
+    pub fn exhausted(&self, usage: &MinterUsage) -> [Option<QuotaLimit>; 2] {
+        [
+            (usage.window_minted >= self.window_limit).then_some(QuotaLimit::Window),
+            (usage.lifetime_minted >= self.lifetime_limit).then_some(QuotaLimit::Lifetime),
+        ]
+    }
+}
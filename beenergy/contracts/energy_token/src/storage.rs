@@ -0,0 +1,46 @@
+//! # Storage Module - Almacenamiento persistente con TTL
+//!
+//! Los vintages de cada holder y la cuota y el uso de cada minter viven en
+//! almacenamiento persistente. Cada lectura o escritura extiende el TTL de la
+//! entrada con los mismos umbrales que `EnergyDistribution`, para que un
+//! holder o un minter inactivo no pierda su estado por archivado.
+//!
+//! Los balances y allowances los extiende `stellar_tokens`.
+
+use soroban_sdk::{Env, IntoVal, TryFromVal, Val};
+
+use crate::DataKey;
+
+/// Ledgers por día (un ledger cada ~5 segundos)
+pub const DAY_IN_LEDGERS: u32 = 17_280;
+
+/// Se extiende una entrada persistente cuando le quedan menos de 30 días
+pub const PERSISTENT_TTL_THRESHOLD: u32 = 30 * DAY_IN_LEDGERS;
+/// Las entradas persistentes se extienden hasta 120 días
+pub const PERSISTENT_TTL_EXTEND_TO: u32 = 120 * DAY_IN_LEDGERS;
+
+/// Lee una entrada persistente y extiende su TTL
+pub fn get<V: TryFromVal<Env, Val>>(e: &Env, key: &DataKey) -> Option<V> {
+    let value = e.storage().persistent().get(key);
+    if value.is_some() {
+        e.storage().persistent().extend_ttl(
+            key,
+            PERSISTENT_TTL_THRESHOLD,
+            PERSISTENT_TTL_EXTEND_TO,
+        );
+    }
+    value
+}
+
+/// Guarda una entrada persistente con el TTL extendido
+pub fn set<V: IntoVal<Env, Val>>(e: &Env, key: &DataKey, value: &V) {
+    e.storage().persistent().set(key, value);
+    e.storage()
+        .persistent()
+        .extend_ttl(key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+}
+
+/// Elimina una entrada persistente
+pub fn remove(e: &Env, key: &DataKey) {
+    e.storage().persistent().remove(key);
+}
@@ -0,0 +1,86 @@
+//! # Vintage Module - Antigüedad del HDROP
+//!
+//! Cada minteo se asigna al vintage (mes) en que se generó la energía:
+//! `vintage = timestamp / VINTAGE_SECONDS`. Cada holder guarda su balance
+//! repartido por vintage (`Map<u32, i128>`, del más viejo al más nuevo) y las
+//! transferencias mueven los vintages originales: transferir no renueva
+//! créditos vencidos.
+//!
+//! Con expiración configurada (`expiry_vintages`), un vintage vence cuando
+//! `vintage + expiry_vintages <= vintage actual`, como los créditos de
+//! excedente del net metering. El balance vencido no se puede transferir ni
+//! quemar, y el admin puede barrerlo (`sweep_expired`).
+//!
+//! Los meses son de 30 días: el contrato no conoce el calendario.
+
+use soroban_sdk::{contracttype, Env, Map};
+
+/// Duración de un vintage en segundos (30 días)
+pub const VINTAGE_SECONDS: u64 = 30 * 24 * 3_600;
+
+/// Balance de un holder en un vintage
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VintageBalance {
+    pub vintage: u32,  // Mes de generación (timestamp / VINTAGE_SECONDS)
+    pub amount: i128,  // Balance de ese vintage
+    pub expired: bool, // Si ya no se puede usar
+}
+
+/// Vintage del ledger actual
+pub fn current(e: &Env) -> u32 {
+    (e.ledger().timestamp() / VINTAGE_SECONDS) as u32
+}
+
+/// Si `vintage` está vencido en `current` (nunca, sin expiración)
+pub fn is_expired(vintage: u32, current: u32, expiry_vintages: Option<u32>) -> bool {
+    expiry_vintages.is_some_and(|expiry| vintage.saturating_add(expiry) <= current)
+}
+
+/// Suma de los vintages que cumplen `filter`
+pub fn total(buckets: &Map<u32, i128>, filter: impl Fn(u32) -> bool) -> i128 {
+    buckets
+        .iter()
+        .filter(|(vintage, _)| filter(*vintage))
+        .map(|(_, amount)| amount)
+        .sum()
+}
+
+/// Saca hasta `amount` de los vintages que cumplen `filter`, del más viejo al
+/// más nuevo, y devuelve lo sacado por vintage
+pub fn take(
+    e: &Env,
+    buckets: &mut Map<u32, i128>,
+    amount: i128,
+    filter: impl Fn(u32) -> bool,
+) -> Map<u32, i128> {
+    let mut taken = Map::new(e);
+    let mut pending = amount;
+
+    for (vintage, balance) in buckets.clone().iter() {
+        if pending <= 0 {
+            break;
+        }
+        if !filter(vintage) {
+            continue;
+        }
+
+        let part = balance.min(pending);
+        if part == balance {
+            buckets.remove(vintage);
+        } else {
+            buckets.set(vintage, balance - part);
+        }
+        taken.set(vintage, part);
+        pending -= part;
+    }
+
+    taken
+}
+
+/// Suma `other` a `buckets`, vintage por vintage
+pub fn add(buckets: &mut Map<u32, i128>, other: &Map<u32, i128>) {
+    for (vintage, amount) in other.iter() {
+        buckets.set(vintage, buckets.get(vintage).unwrap_or(0) + amount);
+    }
+}
@@ -52,7 +52,7 @@ $DISTRIBUTION_WASM = "target/wasm32v1-none/release/energy_distribution.wasm"
 $DISTRIBUTION_WASM_HASH = stellar contract install --wasm $DISTRIBUTION_WASM --source $ADMIN_IDENTITY --network $NETWORK
 Write-Host "Distribution WASM Hash: $DISTRIBUTION_WASM_HASH"
 
-$DISTRIBUTION_CONTRACT_ID = stellar contract deploy --wasm-hash $DISTRIBUTION_WASM_HASH --source $ADMIN_IDENTITY --network $NETWORK
+$DISTRIBUTION_CONTRACT_ID = stellar contract deploy --wasm-hash $DISTRIBUTION_WASM_HASH --source $ADMIN_IDENTITY --network $NETWORK -- --admin $ADMIN_ADDRESS --token_contract $TOKEN_CONTRACT_ID --required_approvals 3
 Write-Host "Distribution Contract ID: $DISTRIBUTION_CONTRACT_ID"
 Write-Host ""
 
@@ -60,9 +60,6 @@ Write-Host ""
 Write-Host "Step 6: Initializing contracts..."
 stellar contract invoke --id $TOKEN_CONTRACT_ID --source $ADMIN_IDENTITY --network $NETWORK -- __constructor --admin $ADMIN_ADDRESS --distribution_contract $DISTRIBUTION_CONTRACT_ID --initial_supply 0
 Write-Host "Token initialized"
-
-stellar contract invoke --id $DISTRIBUTION_CONTRACT_ID --source $ADMIN_IDENTITY --network $NETWORK -- initialize --admin $ADMIN_ADDRESS --token_contract $TOKEN_CONTRACT_ID --required_approvals 3
-Write-Host "Distribution initialized"
 Write-Host ""
 
 # Step 7: Grant permissions
@@ -119,10 +119,15 @@ $DISTRIBUTION_WASM_HASH = stellar contract install `
 Write-Host "✅ Distribution WASM instalado: $DISTRIBUTION_WASM_HASH" -ForegroundColor Green
 
 Write-Host "Desplegando distribución..."
+# La distribución se inicializa en el constructor, al desplegar
 $DISTRIBUTION_CONTRACT_ID = stellar contract deploy `
     --wasm-hash $DISTRIBUTION_WASM_HASH `
     --source $ADMIN_IDENTITY `
-    --network $NETWORK
+    --network $NETWORK `
+    -- `
+    --admin $ADMIN_ADDRESS `
+    --token_contract $TOKEN_CONTRACT_ID `
+    --required_approvals 3
 
 Write-Host "✅ Distribución desplegada: $DISTRIBUTION_CONTRACT_ID" -ForegroundColor Green
 Write-Host ""
@@ -145,20 +150,6 @@ stellar contract invoke `
     --initial_supply 0
 
 Write-Host "✅ Token inicializado" -ForegroundColor Green
-
-# Inicializar Distribution
-Write-Host "Inicializando distribución..."
-stellar contract invoke `
-    --id $DISTRIBUTION_CONTRACT_ID `
-    --source $ADMIN_IDENTITY `
-    --network $NETWORK `
-    -- `
-    initialize `
-    --admin $ADMIN_ADDRESS `
-    --token_contract $TOKEN_CONTRACT_ID `
-    --required_approvals 3
-
-Write-Host "✅ Distribución inicializada" -ForegroundColor Green
 Write-Host ""
 
 # ==============================================================================
@@ -116,10 +116,15 @@ DISTRIBUTION_WASM_HASH=$(stellar contract install \
 echo -e "${GREEN}✅ Distribution WASM instalado: $DISTRIBUTION_WASM_HASH${NC}"
 
 echo "Desplegando distribución..."
+# La distribución se inicializa en el constructor, al desplegar
 DISTRIBUTION_CONTRACT_ID=$(stellar contract deploy \
     --wasm-hash $DISTRIBUTION_WASM_HASH \
     --source $ADMIN_IDENTITY \
-    --network $NETWORK)
+    --network $NETWORK \
+    -- \
+    --admin $ADMIN_ADDRESS \
+    --token_contract $TOKEN_CONTRACT_ID \
+    --required_approvals 3)
 
 echo -e "${GREEN}✅ Distribución desplegada: $DISTRIBUTION_CONTRACT_ID${NC}"
 echo ""
@@ -142,20 +147,6 @@ stellar contract invoke \
     --initial_supply 0
 
 echo -e "${GREEN}✅ Token inicializado${NC}"
-
-# Inicializar Distribution
-echo "Inicializando distribución..."
-stellar contract invoke \
-    --id $DISTRIBUTION_CONTRACT_ID \
-    --source $ADMIN_IDENTITY \
-    --network $NETWORK \
-    -- \
-    initialize \
-    --admin $ADMIN_ADDRESS \
-    --token_contract $TOKEN_CONTRACT_ID \
-    --required_approvals 3
-
-echo -e "${GREEN}✅ Distribución inicializada${NC}"
 echo ""
 
 # ==============================================================================
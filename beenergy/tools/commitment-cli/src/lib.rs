@@ -0,0 +1,268 @@
+//! # Commitment CLI - Commitments de consumo off-chain
+//!
+//! Genera en la máquina del miembro los commitments de consumo que después
+//! se registran con `record_private_consumption`. El secret nunca se envía
+//! a la red: antes existía `generate_commitment_helper` en el contrato, pero
+//! llamarlo (aunque fuera en simulación) mandaba el secret a un nodo RPC.
+//!
+//! El formato es el del módulo `privacy` del contrato
+//! (`encode_consumption_v1` + `generate_commitment`), calculado con `sha2` y
+//! el XDR de las direcciones armado a mano: el binario no incluye el host de
+//! Soroban. Los tests comparan el resultado con el del contrato.
+//!
+//! ## Uso:
+//! ```text
+//! let secret = generate_secret()?;
+//! let data = ConsumptionData { network_passphrase, contract, user, period, consumed_kwh, secret };
+//! let commitment = commitment_v1(&data)?;   // -> record_private_consumption
+//! let opening = opening_json(&data);        // -> verify_private_consumption / reveal
+//! ```
+
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// Passphrase de la red de pruebas
+pub const TESTNET_PASSPHRASE: &str = "Test SDF Network ; September 2015";
+
+/// Passphrase de la red principal
+pub const MAINNET_PASSPHRASE: &str = "Public Global Stellar Network ; September 2015";
+
+/// Dominio del formato v1 (`privacy::COMMITMENT_DOMAIN`)
+const COMMITMENT_DOMAIN: &[u8] = b"BEENERGY-CONSUMPTION";
+
+/// Versión del formato (`privacy::COMMITMENT_VERSION`)
+const COMMITMENT_VERSION: u8 = 1;
+
+/// Discriminantes XDR de `ScVal::Address`, `ScAddress` y `PublicKey`
+const SCV_ADDRESS: u32 = 18;
+const SC_ADDRESS_TYPE_ACCOUNT: u32 = 0;
+const SC_ADDRESS_TYPE_CONTRACT: u32 = 1;
+const PUBLIC_KEY_TYPE_ED25519: u32 = 0;
+
+/// Errores al generar commitments
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CommitmentError {
+    /// La dirección no es una cuenta (G...) ni un contrato (C...) válido
+    InvalidAddress(String),
+    /// El secret no son 32 bytes en hexadecimal
+    InvalidSecret,
+    /// El consumo no puede ser negativo
+    NegativeConsumption,
+    /// El sistema operativo no pudo generar bytes aleatorios
+    Randomness,
+}
+
+impl fmt::Display for CommitmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidAddress(address) => write!(f, "dirección inválida: {address}"),
+            Self::InvalidSecret => write!(f, "el secret debe ser 32 bytes en hexadecimal"),
+            Self::NegativeConsumption => write!(f, "el consumo no puede ser negativo"),
+            Self::Randomness => write!(f, "no se pudieron generar bytes aleatorios"),
+        }
+    }
+}
+
+impl std::error::Error for CommitmentError {}
+
+/// Datos que entran en un commitment de consumo v1
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsumptionData {
+    pub network_passphrase: String, // Passphrase de la red (define el network id)
+    pub contract: String,           // Contrato energy_distribution (C...)
+    pub user: String,               // Miembro que registra el consumo (G... o C...)
+    pub period: u64,                // Período del consumo
+    pub consumed_kwh: i128,         // Consumo en unidades de 7 decimales
+    pub secret: [u8; 32],           // Secret del miembro, nunca sale de su máquina
+}
+
+/// Network id de una red: SHA256 de su passphrase
+pub fn network_id(passphrase: &str) -> [u8; 32] {
+    Sha256::digest(passphrase.as_bytes()).into()
+}
+
+/// Genera un secret aleatorio de 32 bytes con el RNG del sistema operativo
+pub fn generate_secret() -> Result<[u8; 32], CommitmentError> {
+    let mut secret = [0u8; 32];
+    getrandom::getrandom(&mut secret).map_err(|_| CommitmentError::Randomness)?;
+    Ok(secret)
+}
+
+/// Lee un secret en hexadecimal (con o sin prefijo `0x`)
+pub fn parse_secret(secret: &str) -> Result<[u8; 32], CommitmentError> {
+    let secret = secret.strip_prefix("0x").unwrap_or(secret);
+    let bytes = hex::decode(secret).map_err(|_| CommitmentError::InvalidSecret)?;
+    bytes.try_into().map_err(|_| CommitmentError::InvalidSecret)
+}
+
+/// Calcula el commitment v1 que el contrato espera para estos datos
+pub fn commitment_v1(data: &ConsumptionData) -> Result<[u8; 32], CommitmentError> {
+    if data.consumed_kwh < 0 {
+        return Err(CommitmentError::NegativeConsumption);
+    }
+
+    let mut user_data = COMMITMENT_DOMAIN.to_vec();
+    user_data.push(COMMITMENT_VERSION);
+    user_data.extend_from_slice(&network_id(&data.network_passphrase));
+    user_data.extend_from_slice(&address_xdr(&data.contract)?);
+    user_data.extend_from_slice(&address_xdr(&data.user)?);
+    user_data.extend_from_slice(&data.period.to_be_bytes());
+    user_data.extend_from_slice(&data.consumed_kwh.to_be_bytes());
+    user_data.extend_from_slice(&data.secret);
+
+    Ok(Sha256::digest(&user_data).into())
+}
+
+/// Apertura `ConsumptionOpening::V1` en el formato JSON de `stellar contract invoke`
+pub fn opening_json(data: &ConsumptionData) -> String {
+    format!(
+        "{{\"V1\":[\"{}\",\"{}\"]}}",
+        data.consumed_kwh,
+        hex::encode(data.secret)
+    )
+}
+
+/// XDR de una dirección como `ScVal::Address` (igual que `Address::to_xdr`)
+fn address_xdr(strkey: &str) -> Result<Vec<u8>, CommitmentError> {
+    let mut xdr = SCV_ADDRESS.to_be_bytes().to_vec();
+    match stellar_strkey::Strkey::from_string(strkey) {
+        Ok(stellar_strkey::Strkey::PublicKeyEd25519(key)) => {
+            xdr.extend_from_slice(&SC_ADDRESS_TYPE_ACCOUNT.to_be_bytes());
+            xdr.extend_from_slice(&PUBLIC_KEY_TYPE_ED25519.to_be_bytes());
+            xdr.extend_from_slice(&key.0);
+        }
+        Ok(stellar_strkey::Strkey::Contract(contract)) => {
+            xdr.extend_from_slice(&SC_ADDRESS_TYPE_CONTRACT.to_be_bytes());
+            xdr.extend_from_slice(&contract.0);
+        }
+        _ => return Err(CommitmentError::InvalidAddress(strkey.into())),
+    }
+    Ok(xdr)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use energy_distribution::{
+        privacy, ConsumptionOpening, EnergyDistribution, EnergyDistributionClient, PERIOD_SECONDS,
+    };
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        vec, Address, BytesN, Env,
+    };
+
+    #[test]
+    fn test_offline_commitment_is_accepted_by_contract() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().set_network_id(network_id(TESTNET_PASSPHRASE));
+        env.ledger()
+            .with_mut(|li| li.timestamp = 48 * PERIOD_SECONDS);
+
+        let admin = Address::generate(&env);
+        let token = Address::generate(&env);
+        let member = Address::generate(&env);
+        let other = Address::generate(&env);
+        let contract_id = env.register(EnergyDistribution, (&admin, &token, &1u32));
+        let client = EnergyDistributionClient::new(&env, &contract_id);
+        client.add_members_multisig(
+            &vec![&env, member.clone()],
+            &vec![&env, member.clone(), other],
+            &vec![&env, 6_000, 4_000],
+        );
+        client.enable_privacy(&admin);
+
+        let data = ConsumptionData {
+            network_passphrase: TESTNET_PASSPHRASE.into(),
+            contract: contract_id.to_string().to_string(),
+            user: member.to_string().to_string(),
+            period: 47,
+            consumed_kwh: 12_5000000,
+            secret: generate_secret().unwrap(),
+        };
+        let commitment = BytesN::from_array(&env, &commitment_v1(&data).unwrap());
+        client.record_private_consumption(&member, &47, &commitment);
+
+        let opening =
+            ConsumptionOpening::V1(data.consumed_kwh, BytesN::from_array(&env, &data.secret));
+        assert!(client.verify_private_consumption(&member, &47, &0, &opening));
+
+        // En otra red el mismo secret produce otro commitment
+        let mainnet = ConsumptionData {
+            network_passphrase: MAINNET_PASSPHRASE.into(),
+            ..data.clone()
+        };
+        assert_ne!(commitment_v1(&mainnet).unwrap(), commitment.to_array());
+    }
+
+    #[test]
+    fn test_account_commitment_matches_contract_encoding() {
+        let env = Env::default();
+        let data = ConsumptionData {
+            network_passphrase: MAINNET_PASSPHRASE.into(),
+            contract: Address::generate(&env).to_string().to_string(),
+            user: "GBZXN7PIRZGNMHGA7MUUUF4GWPY5AYPV6LY4UV2GL6VJGIQRXFDNMADI".into(),
+            period: 480,
+            consumed_kwh: 3_2500000,
+            secret: [9u8; 32],
+        };
+
+        let user_data = privacy::encode_consumption_v1(
+            &env,
+            &BytesN::from_array(&env, &network_id(&data.network_passphrase)),
+            &Address::from_str(&env, &data.contract),
+            &Address::from_str(&env, &data.user),
+            data.period,
+            data.consumed_kwh,
+            &BytesN::from_array(&env, &data.secret),
+        );
+        let expected = privacy::generate_commitment(&env, &user_data);
+        assert_eq!(commitment_v1(&data).unwrap(), expected.to_array());
+    }
+
+    #[test]
+    fn test_invalid_inputs_are_rejected() {
+        let data = ConsumptionData {
+            network_passphrase: TESTNET_PASSPHRASE.into(),
+            contract: "CNOTACONTRACT".into(),
+            user: "GBZXN7PIRZGNMHGA7MUUUF4GWPY5AYPV6LY4UV2GL6VJGIQRXFDNMADI".into(),
+            period: 1,
+            consumed_kwh: 1,
+            secret: [7u8; 32],
+        };
+        assert_eq!(
+            commitment_v1(&data),
+            Err(CommitmentError::InvalidAddress("CNOTACONTRACT".into()))
+        );
+        assert_eq!(
+            commitment_v1(&ConsumptionData {
+                consumed_kwh: -1,
+                ..data
+            }),
+            Err(CommitmentError::NegativeConsumption)
+        );
+
+        assert_eq!(
+            parse_secret(&format!("0x{}", "07".repeat(32))),
+            Ok([7u8; 32])
+        );
+        assert_eq!(parse_secret("07"), Err(CommitmentError::InvalidSecret));
+        assert_eq!(parse_secret("zz"), Err(CommitmentError::InvalidSecret));
+    }
+
+    #[test]
+    fn test_opening_json_format() {
+        let data = ConsumptionData {
+            network_passphrase: TESTNET_PASSPHRASE.into(),
+            contract: String::new(),
+            user: String::new(),
+            period: 0,
+            consumed_kwh: 10_0000000,
+            secret: [0xab; 32],
+        };
+        assert_eq!(
+            opening_json(&data),
+            format!("{{\"V1\":[\"100000000\",\"{}\"]}}", "ab".repeat(32))
+        );
+    }
+}
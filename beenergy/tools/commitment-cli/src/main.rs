@@ -0,0 +1,125 @@
+//! # beenergy-commitment
+//!
+//! Línea de comandos para preparar el consumo privado sin exponer el secret.
+//!
+//! ```text
+//! beenergy-commitment secret
+//! beenergy-commitment commit --contract C... --user G... --period 480 --kwh 125000000 [--secret <hex>] [--network testnet|mainnet|<passphrase>]
+//! beenergy-commitment open   --kwh 125000000 --secret <hex>
+//! ```
+//!
+//! `commit` imprime el commitment (para `record_private_consumption` o
+//! `commit_settlement`) y la apertura V1 (para `verify_private_consumption`).
+//! Si no se pasa `--secret`, genera uno nuevo: guardalo, sin él no se puede
+//! abrir el commitment.
+
+use commitment_cli::{
+    commitment_v1, generate_secret, opening_json, parse_secret, CommitmentError, ConsumptionData,
+    MAINNET_PASSPHRASE, TESTNET_PASSPHRASE,
+};
+use std::collections::HashMap;
+use std::process::ExitCode;
+
+const USAGE: &str = "uso:
+  beenergy-commitment secret
+  beenergy-commitment commit --contract <C...> --user <G...> --period <n> --kwh <n> [--secret <hex>] [--network testnet|mainnet|<passphrase>]
+  beenergy-commitment open --kwh <n> --secret <hex>";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(output) => {
+            println!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<String, String> {
+    let (command, rest) = args.split_first().ok_or("falta el comando")?;
+    let flags = parse_flags(rest)?;
+
+    match command.as_str() {
+        "secret" => Ok(hex::encode(generate_secret().map_err(error)?)),
+        "commit" => {
+            let secret = match flags.get("secret") {
+                Some(secret) => parse_secret(secret).map_err(error)?,
+                None => generate_secret().map_err(error)?,
+            };
+            let data = ConsumptionData {
+                network_passphrase: network_passphrase(flags.get("network")),
+                contract: required(&flags, "contract")?.into(),
+                user: required(&flags, "user")?.into(),
+                period: number(&flags, "period")?,
+                consumed_kwh: number(&flags, "kwh")?,
+                secret,
+            };
+            let commitment = commitment_v1(&data).map_err(error)?;
+
+            Ok(format!(
+                "commitment: {}\nsecret:     {}\nopening:    {}",
+                hex::encode(commitment),
+                hex::encode(data.secret),
+                opening_json(&data)
+            ))
+        }
+        "open" => {
+            let data = ConsumptionData {
+                network_passphrase: String::new(),
+                contract: String::new(),
+                user: String::new(),
+                period: 0,
+                consumed_kwh: number(&flags, "kwh")?,
+                secret: parse_secret(required(&flags, "secret")?).map_err(error)?,
+            };
+            Ok(opening_json(&data))
+        }
+        other => Err(format!("comando desconocido: {other}")),
+    }
+}
+
+/// Lee pares `--nombre valor`
+fn parse_flags(args: &[String]) -> Result<HashMap<String, String>, String> {
+    let mut flags = HashMap::new();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let name = flag
+            .strip_prefix("--")
+            .ok_or_else(|| format!("argumento inesperado: {flag}"))?;
+        let value = args
+            .next()
+            .ok_or_else(|| format!("falta el valor de --{name}"))?;
+        flags.insert(name.to_string(), value.clone());
+    }
+    Ok(flags)
+}
+
+fn required<'a>(flags: &'a HashMap<String, String>, name: &str) -> Result<&'a str, String> {
+    flags
+        .get(name)
+        .map(String::as_str)
+        .ok_or_else(|| format!("falta --{name}"))
+}
+
+fn number<T: std::str::FromStr>(flags: &HashMap<String, String>, name: &str) -> Result<T, String> {
+    required(flags, name)?
+        .parse()
+        .map_err(|_| format!("--{name} debe ser un número entero"))
+}
+
+/// `testnet` (por defecto), `mainnet` o una passphrase arbitraria
+fn network_passphrase(network: Option<&String>) -> String {
+    match network.map(String::as_str) {
+        None | Some("testnet") => TESTNET_PASSPHRASE.into(),
+        Some("mainnet") => MAINNET_PASSPHRASE.into(),
+        Some(passphrase) => passphrase.into(),
+    }
+}
+
+fn error(error: CommitmentError) -> String {
+    error.to_string()
+}
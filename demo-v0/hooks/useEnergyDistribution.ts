@@ -5,6 +5,13 @@ import { useWallet } from "@/lib/wallet-context"
 import { CONTRACTS, STELLAR_CONFIG, NETWORK_PASSPHRASE } from "@/lib/contracts-config"
 import * as StellarSdk from "@stellar/stellar-sdk"
 
+/** Contract generation periods are hourly slots: unix timestamp / 3600 */
+export const PERIOD_SECONDS = 3600
+
+export function currentPeriod(): number {
+  return Math.floor(Date.now() / 1000 / PERIOD_SECONDS)
+}
+
 export function useEnergyDistribution() {
   const { address, kit } = useWallet()
   const [isLoading, setIsLoading] = useState(false)
@@ -31,14 +38,14 @@ export function useEnergyDistribution() {
       const contract = new StellarSdk.Contract(CONTRACTS.ENERGY_DISTRIBUTION)
       const account = await server.getAccount(targetAddress)
 
-      // Get member percent
+      // Get member share (basis points, 10000 = 100%)
       const percentTx = new StellarSdk.TransactionBuilder(account, {
         fee: StellarSdk.BASE_FEE,
         networkPassphrase: NETWORK_PASSPHRASE,
       })
         .addOperation(
           contract.call(
-            "get_member_percent",
+            "get_member_share",
             StellarSdk.nativeToScVal(targetAddress, { type: "address" })
           )
         )
@@ -48,10 +55,10 @@ export function useEnergyDistribution() {
       const percentResult = await server.simulateTransaction(percentTx)
 
       if (StellarSdk.SorobanRpc.Api.isSimulationSuccess(percentResult)) {
-        const percent = StellarSdk.scValToNative(percentResult.result!.retval)
+        const shareBps = StellarSdk.scValToNative(percentResult.result!.retval)
         return {
-          isMember: percent !== null,
-          percent: percent || 0,
+          isMember: shareBps !== null,
+          percent: (shareBps || 0) / 100,
         }
       }
 
@@ -114,9 +121,72 @@ export function useEnergyDistribution() {
   }
 
   /**
-   * Record energy generation (Admin only)
+   * Get on-chain generation history between two hourly periods (inclusive)
+   */
+  const getGenerationHistory = async (
+    fromPeriod: number,
+    toPeriod: number
+  ): Promise<{ period: number; kwh: number; recordedAt: number }[]> => {
+    try {
+      setIsLoading(true)
+      setError(null)
+
+      if (!address) {
+        throw new Error("No wallet connected")
+      }
+
+      if (!CONTRACTS.ENERGY_DISTRIBUTION) {
+        throw new Error("Energy distribution contract not configured")
+      }
+
+      const server = new StellarSdk.SorobanRpc.Server(STELLAR_CONFIG.RPC_URL)
+      const contract = new StellarSdk.Contract(CONTRACTS.ENERGY_DISTRIBUTION)
+      const account = await server.getAccount(address)
+
+      const transaction = new StellarSdk.TransactionBuilder(account, {
+        fee: StellarSdk.BASE_FEE,
+        networkPassphrase: NETWORK_PASSPHRASE,
+      })
+        .addOperation(
+          contract.call(
+            "get_generation_history",
+            StellarSdk.nativeToScVal(fromPeriod, { type: "u64" }),
+            StellarSdk.nativeToScVal(toPeriod, { type: "u64" })
+          )
+        )
+        .setTimeout(30)
+        .build()
+
+      const simulatedResult = await server.simulateTransaction(transaction)
+
+      if (StellarSdk.SorobanRpc.Api.isSimulationSuccess(simulatedResult)) {
+        const records = StellarSdk.scValToNative(simulatedResult.result!.retval)
+        // Convert from 7 decimals to readable format
+        return records.map((record: { period: bigint; kwh: bigint; recorded_at: bigint }) => ({
+          period: Number(record.period),
+          kwh: Number(record.kwh) / 10000000,
+          recordedAt: Number(record.recorded_at),
+        }))
+      }
+
+      throw new Error("Failed to get generation history")
+    } catch (err) {
+      const errorMessage = err instanceof Error ? err.message : "Unknown error"
+      setError(errorMessage)
+      console.error("Error getting generation history:", err)
+      throw err
+    } finally {
+      setIsLoading(false)
+    }
+  }
+
+  /**
+   * Record energy generation for an hourly period (Admin only)
    */
-  const recordGeneration = async (kwhGenerated: number): Promise<string> => {
+  const recordGeneration = async (
+    kwhGenerated: number,
+    period: number = currentPeriod()
+  ): Promise<string> => {
     try {
       setIsLoading(true)
       setError(null)
@@ -143,6 +213,8 @@ export function useEnergyDistribution() {
         .addOperation(
           contract.call(
             "record_generation",
+            StellarSdk.nativeToScVal(address, { type: "address" }),
+            StellarSdk.nativeToScVal(period, { type: "u64" }),
             StellarSdk.nativeToScVal(kwhInStroops, { type: "i128" })
           )
         )
@@ -185,6 +257,7 @@ export function useEnergyDistribution() {
   return {
     getMemberInfo,
     getTotalGenerated,
+    getGenerationHistory,
     recordGeneration,
     isLoading,
     error,